@@ -0,0 +1,87 @@
+//! Per-source circuit breakers. After [`THRESHOLD`] consecutive failures a source
+//! is skipped for [`COOLDOWN`]; the next request after that decides whether it
+//! closes again or stays open. State lives in `breakers.json` under the cache dir,
+//! so batch runs and cron jobs share it.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use crate::client::{self, Source};
+use crate::i18n;
+use crate::util;
+
+/// Consecutive failures that open a breaker.
+pub const THRESHOLD: u32 = 3;
+/// How long an open breaker skips its source.
+pub const COOLDOWN: Duration = Duration::from_secs(10 * 60);
+
+const STATE_FILE: &str = "breakers.json";
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Breaker {
+    failures: u32,
+    /// Unix time the breaker (re)opened
+    opened_at: Option<u64>,
+}
+
+type State = BTreeMap<String, Breaker>;
+
+static STATE: OnceLock<Mutex<State>> = OnceLock::new();
+
+fn state() -> &'static Mutex<State> {
+    STATE.get_or_init(|| Mutex::new(util::read_state(STATE_FILE)))
+}
+
+/// Time left before an open breaker lets `source` be tried again; `None` when closed.
+pub fn retry_in(source: Source) -> Option<Duration> {
+    let state = state().lock().ok()?;
+    let until = state.get(source.name())?.opened_at? + COOLDOWN.as_secs();
+    let now = util::now_secs();
+    (now < until).then(|| Duration::from_secs(until - now))
+}
+
+/// Whether requests to `source` may go out; always true for clients built with
+/// [`crate::ClientBuilder::circuit_breaker`] off.
+pub(crate) fn allows(source: Source) -> bool {
+    if !client::settings().circuit_breaker { return true; }
+    match retry_in(source) {
+        Some(left) => {
+            util::debug(format!("{:?}: circuit open, skipped for another {}s", source, left.as_secs()));
+            false
+        }
+        None => true,
+    }
+}
+
+/// Fail when the breaker of `source` is open.
+pub(crate) fn check(source: Source) -> Result<()> {
+    if !allows(source) {
+        let mins = retry_in(source).map_or(0, |d| d.as_secs().div_ceil(60));
+        anyhow::bail!(i18n::tf("err.circuit_open", &[&format!("{:?}", source), &mins]));
+    }
+    Ok(())
+}
+
+/// Count a request outcome against `source`'s breaker.
+pub(crate) fn record(source: Source, ok: bool) {
+    let Ok(mut state) = state().lock() else { return };
+    if ok {
+        // Only a recovery changes anything worth writing
+        if let Some(b) = state.remove(source.name()) {
+            if b.opened_at.is_some() { util::debug(format!("{:?}: circuit closed", source)); }
+            util::write_state(STATE_FILE, &*state);
+        }
+        return;
+    }
+    let b = state.entry(source.name().to_string()).or_default();
+    b.failures += 1;
+    if b.failures >= THRESHOLD {
+        b.opened_at = Some(util::now_secs());
+        util::debug(format!("{:?}: circuit open after {} failures", source, b.failures));
+    }
+    util::write_state(STATE_FILE, &*state);
+}
@@ -0,0 +1,189 @@
+//! Censored/uncensored classification of titles from several signals: the code
+//! family (HEYZO, 1pondo, FC2…), known uncensored studios, source tags, and hints
+//! the sources give directly (DMM only sells censored titles, JavDB files
+//! uncensored ones in their own section).
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+
+use crate::types::AvDetail;
+use crate::util;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[non_exhaustive]
+pub enum Censorship {
+    Censored,
+    Uncensored,
+    /// Censored release whose unmosaicked master leaked
+    Leaked,
+    #[default]
+    Unknown,
+}
+
+/// Category of a title and how sure the classifier is about it.
+#[derive(Debug, Clone, Copy, PartialEq, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[non_exhaustive]
+pub struct Classification {
+    pub category: Censorship,
+    /// From 0.0 (no signal) to 1.0 (certain)
+    pub confidence: f32,
+}
+
+impl Classification {
+    pub fn new(category: Censorship, confidence: f32) -> Self {
+        Self { category, confidence }
+    }
+
+    pub fn is_uncensored(&self) -> bool {
+        self.category == Censorship::Uncensored
+    }
+
+    /// The more confident of the two.
+    fn or(self, other: Classification) -> Classification {
+        if other.confidence > self.confidence { other } else { self }
+    }
+}
+
+/// Code patterns of uncensored studios, with how reliably each implies uncensored.
+const UNCENSORED_FAMILIES: &[(&str, f32)] = &[
+    (r"^HEYZO[-_ ]?\d{3,4}$", 0.95),
+    (r"^HEYDOUGA[-_ ]?\d{4}[-_]\d+$", 0.95),
+    (r"^(?:TOKYO[-_ ]?HOT[-_ ]?)?[NK]\d{4}$", 0.9),
+    // Caribbeancom `MMDDYY-NNN`, 1pondo/Pacopacomama `MMDDYY_NNN`, 10musume `MMDDYY_NN`
+    (r"^(?:CARIB(?:BEANCOM)?[-_ ]?)?\d{6}-\d{3}$", 0.9),
+    (r"^(?:1PON(?:DO)?|PACO(?:PACOMAMA)?)?[-_ ]?\d{6}_\d{3}$", 0.9),
+    (r"^(?:10MU(?:SUME)?[-_ ]?)?\d{6}_\d{2}$", 0.9),
+    // Mostly amateur uploads without mosaic, but not all of them
+    (r"^FC2[-_ ]?(?:PPV[-_ ]?)?\d{5,8}$", 0.8),
+];
+
+/// `ABC-123` codes are DMM-distributed labels, which are censored by law.
+const CENSORED_CODE: &str = r"^[A-Z]{2,6}-\d{2,5}$";
+
+const UNCENSORED_STUDIOS: &[&str] = &[
+    "heyzo",
+    "一本道",
+    "1pondo",
+    "カリビアンコム",
+    "caribbeancom",
+    "東京熱",
+    "tokyo-hot",
+    "tokyo hot",
+    "パコパコママ",
+    "pacopacomama",
+    "天然むすめ",
+    "10musume",
+    "heydouga",
+    "fc2",
+    "金髪天國",
+    "kin8tengoku",
+];
+
+const LEAK_WORDS: &[&str] = &["流出", "leak", "リーク", "破解", "decensored", "reducing mosaic"];
+
+fn mentions_leak(text: &str) -> bool {
+    let lower = text.to_lowercase();
+    LEAK_WORDS.iter().any(|w| lower.contains(w))
+}
+
+/// [`UNCENSORED_FAMILIES`] and [`CENSORED_CODE`], compiled.
+fn code_patterns() -> &'static (Vec<(Regex, f32)>, Regex) {
+    static PATTERNS: OnceLock<(Vec<(Regex, f32)>, Regex)> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        let families = UNCENSORED_FAMILIES.iter().map(|(p, c)| (Regex::new(p).unwrap(), *c)).collect();
+        (families, Regex::new(CENSORED_CODE).unwrap())
+    })
+}
+
+/// Signal of the code alone.
+fn by_code(code: &str) -> Classification {
+    let code = code.trim().to_uppercase();
+    let (families, censored) = code_patterns();
+    if let Some((_, confidence)) = families.iter().find(|(re, _)| re.is_match(&code)) {
+        return Classification::new(Censorship::Uncensored, *confidence);
+    }
+    if censored.is_match(&code) {
+        return Classification::new(Censorship::Censored, 0.6);
+    }
+    Classification::default()
+}
+
+/// Classify from the code, title, studio and tags.
+pub fn classify(code: &str, title: &str, studio: Option<&str>, genres: &[String]) -> Classification {
+    let code_signal = by_code(code);
+    let claims_uncensored = util::looks_uncensored(title) || genres.iter().any(|g| util::looks_uncensored(g));
+    let leak = mentions_leak(title) || genres.iter().any(|g| mentions_leak(g));
+    // An uncensored copy of a censored code can only be a leak
+    if code_signal.category == Censorship::Censored && (claims_uncensored || leak) {
+        return Classification::new(Censorship::Leaked, if leak { 0.8 } else { 0.6 });
+    }
+    let mut best = code_signal;
+    let studio = studio.map(str::to_lowercase).unwrap_or_default();
+    if !studio.is_empty() && UNCENSORED_STUDIOS.iter().any(|s| studio.contains(s)) {
+        best = best.or(Classification::new(Censorship::Uncensored, 0.9));
+    }
+    if genres.iter().any(|g| util::looks_uncensored(g)) {
+        best = best.or(Classification::new(Censorship::Uncensored, 0.85));
+    }
+    if util::looks_uncensored(title) {
+        best = best.or(Classification::new(Censorship::Uncensored, 0.6));
+    }
+    best
+}
+
+/// Classify a detail, keeping the hint its source already set when that is more confident.
+pub fn detail(d: &AvDetail) -> Classification {
+    let found = classify(&d.code, &d.title, d.studio.as_deref(), &d.genres);
+    // Leaks hide behind censored-looking source data, so they win over the hint
+    if found.category == Censorship::Leaked { found } else { d.censorship.or(found) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn category(code: &str, title: &str, studio: Option<&str>, genres: &[&str]) -> Censorship {
+        let genres: Vec<String> = genres.iter().map(|g| g.to_string()).collect();
+        classify(code, title, studio, &genres).category
+    }
+
+    #[test]
+    fn code_families() {
+        for (code, confidence) in [
+            ("HEYZO-1234", 0.95),
+            ("heydouga-4017-123", 0.95),
+            ("Tokyo-Hot n1234", 0.9),
+            ("K1234", 0.9),
+            ("carib-010120-001", 0.9),
+            ("010120-001", 0.9),
+            ("1pondo_010120_001", 0.9),
+            ("paco-010120_123", 0.9),
+            ("10mu-010120_01", 0.9),
+            ("FC2-PPV-1234567", 0.8),
+            ("fc2 1234567", 0.8),
+        ] {
+            assert_eq!(by_code(code), Classification::new(Censorship::Uncensored, confidence), "{}", code);
+        }
+        assert_eq!(by_code("SSIS-001"), Classification::new(Censorship::Censored, 0.6));
+        assert_eq!(by_code("not a code"), Classification::default());
+    }
+
+    #[test]
+    fn uncensored_claim_on_censored_code_is_a_leak() {
+        assert_eq!(category("SSIS-001", "SSIS-001 無修正", None, &[]), Censorship::Leaked);
+        assert_eq!(category("SSIS-001", "title", None, &["流出"]), Censorship::Leaked);
+        assert_eq!(classify("SSIS-001", "SSIS-001 leaked", None, &[]).confidence, 0.8);
+        assert_eq!(category("SSIS-001", "plain title", Some("S1 NO.1 STYLE"), &[]), Censorship::Censored);
+    }
+
+    #[test]
+    fn studio_and_tags() {
+        assert_eq!(category("ABC123", "title", Some("一本道"), &[]), Censorship::Uncensored);
+        assert_eq!(category("ABC123", "title", None, &["無碼"]), Censorship::Uncensored);
+        assert_eq!(category("ABC123", "title", None, &[]), Censorship::Unknown);
+    }
+}
@@ -0,0 +1,520 @@
+use anyhow::{Context, Result};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::future::Future;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::io::AsyncWriteExt;
+
+use crate::classify;
+use crate::code::CodeKey;
+use crate::genres;
+use crate::i18n::{self, Lang};
+use crate::scraper::{self, BadRequest, NotFound};
+use crate::sources::defs;
+use crate::title;
+use crate::sources::dmm::{self, Entry, Facet};
+use crate::translate::{self, Translator};
+use crate::types::{ActorPage, AvDetail, AvItem, Image, ReviewPage};
+use crate::util;
+
+const DEFAULT_UA: &str =
+    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/125.0 Safari/537.36";
+
+/// Metadata sources a [`Client`] may query. Lookups try them in this order and
+/// skip the ones that are not enabled.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Source {
+    /// DMM Web Service; also needs credentials, see [`ClientBuilder::dmm_credentials`]
+    Dmm,
+    JavDb,
+    JavLibrary,
+    Sukebei,
+}
+
+impl Source {
+    pub const ALL: [Source; 4] = [Source::Dmm, Source::JavDb, Source::JavLibrary, Source::Sukebei];
+
+    /// Lowercase identifier, as used for definition files and persisted state.
+    pub fn name(self) -> &'static str {
+        match self {
+            Source::Dmm => "dmm",
+            Source::JavDb => "javdb",
+            Source::JavLibrary => "javlibrary",
+            Source::Sukebei => "sukebei",
+        }
+    }
+}
+
+/// Resolved configuration shared by every request a client makes.
+#[derive(Debug, Clone)]
+pub(crate) struct Settings {
+    pub(crate) sources: Vec<Source>,
+    pub(crate) user_agent: String,
+    pub(crate) proxy: Option<String>,
+    pub(crate) timeout: Option<Duration>,
+    /// Base URLs replacing a source definition's `mirrors`, in order of preference
+    pub(crate) mirrors: HashMap<Source, Vec<String>>,
+    pub(crate) javdb_cookie: Option<String>,
+    pub(crate) dmm: Option<(String, String)>,
+    pub(crate) circuit_breaker: bool,
+    /// Languages to fetch metadata in; the first is the one details are presented in
+    pub(crate) languages: Vec<Lang>,
+    pub(crate) translator: Option<Translator>,
+}
+
+impl Settings {
+    pub(crate) fn has(&self, source: Source) -> bool {
+        self.sources.contains(&source) && (source != Source::Dmm || self.dmm.is_some())
+    }
+
+    /// Base URLs of an HTML source, in configured order.
+    pub(crate) fn mirrors(&self, source: Source) -> Vec<String> {
+        if let Some(bases) = self.mirrors.get(&source) { return bases.clone(); }
+        if source == Source::Dmm { return Vec::new(); }
+        defs::get(source.name()).mirrors.clone()
+    }
+
+    /// reqwest builder with the user agent, proxy and timeout applied.
+    pub(crate) fn http_builder(&self) -> reqwest::ClientBuilder {
+        let mut builder = reqwest::Client::builder().user_agent(&self.user_agent);
+        if let Some(px) = self.proxy.as_deref().and_then(|p| reqwest::Proxy::all(p).ok()) {
+            builder = builder.proxy(px);
+        }
+        if let Some(t) = self.timeout {
+            builder = builder.timeout(t);
+        }
+        builder
+    }
+}
+
+tokio::task_local! {
+    static CURRENT: Arc<Settings>;
+}
+
+static ENV_SETTINGS: OnceLock<Arc<Settings>> = OnceLock::new();
+
+/// Settings of the client driving the current call; outside of a [`Client`]
+/// call the environment-derived defaults apply.
+pub(crate) fn settings() -> Arc<Settings> {
+    CURRENT
+        .try_with(Arc::clone)
+        .unwrap_or_else(|_| ENV_SETTINGS.get_or_init(|| Arc::new(ClientBuilder::from_env().settings)).clone())
+}
+
+/// Builder for [`Client`].
+#[derive(Debug, Clone)]
+pub struct ClientBuilder {
+    settings: Settings,
+    cache_ttl: Option<Duration>,
+}
+
+impl Default for ClientBuilder {
+    /// Every source enabled, no proxy, no timeout, no cache, no DMM credentials, circuit breakers on,
+    /// metadata in Simplified Chinese (see [`ClientBuilder::language`]), no translator.
+    fn default() -> Self {
+        Self {
+            settings: Settings {
+                sources: Source::ALL.to_vec(),
+                user_agent: DEFAULT_UA.to_string(),
+                proxy: None,
+                timeout: None,
+                mirrors: HashMap::new(),
+                javdb_cookie: None,
+                dmm: None,
+                circuit_breaker: true,
+                languages: vec![Lang::ZhHans],
+                translator: None,
+            },
+            cache_ttl: None,
+        }
+    }
+}
+
+impl ClientBuilder {
+    /// Defaults overridden by the CLI's environment variables: `AV_HTTP_PROXY`, `AV_JAVDB_COOKIE`,
+    /// comma-separated mirror lists in `AV_JAVDB_BASE` / `AV_JAVLIBRARY_BASE` / `AV_SUKEBEI_BASE`,
+    /// DMM via `AV_USE_DMM=1` plus `DMM_API_ID` / `DMM_AFFILIATE_ID`, extra metadata
+    /// languages after the preferred one in `AV_LANGUAGES` (e.g. `ja,en`), and a translator
+    /// per [`Translator::from_env`].
+    pub fn from_env() -> Self {
+        let var = |k: &str| std::env::var(k).ok().filter(|v| !v.is_empty());
+        let mut b = Self::default();
+        b.settings.proxy = var("AV_HTTP_PROXY");
+        for (source, key) in [(Source::JavDb, "AV_JAVDB_BASE"), (Source::JavLibrary, "AV_JAVLIBRARY_BASE"), (Source::Sukebei, "AV_SUKEBEI_BASE")] {
+            if let Some(list) = var(key) { b = b.mirrors(source, list.split(',').map(str::trim).filter(|s| !s.is_empty())); }
+        }
+        b.settings.javdb_cookie = var("AV_JAVDB_COOKIE").map(|c| c.trim().to_string());
+        b.settings.dmm = var("DMM_API_ID").zip(var("DMM_AFFILIATE_ID"));
+        if var("AV_USE_DMM").as_deref() != Some("1") {
+            b.settings.sources.retain(|s| *s != Source::Dmm);
+        }
+        if let Some(list) = var("AV_LANGUAGES") {
+            let extra = list.split(',').filter_map(|t| Lang::from_tag(t.trim()));
+            let preferred = b.settings.languages[0];
+            b = b.languages(std::iter::once(preferred).chain(extra));
+        }
+        b.settings.translator = Translator::from_env();
+        b
+    }
+
+    /// Sources to query; order does not matter.
+    pub fn sources(mut self, sources: impl IntoIterator<Item = Source>) -> Self {
+        self.settings.sources = sources.into_iter().collect();
+        self
+    }
+
+    pub fn user_agent(mut self, ua: impl Into<String>) -> Self {
+        self.settings.user_agent = ua.into();
+        self
+    }
+
+    /// Proxy URL for every request (`http://`, `https://` or `socks5://`).
+    pub fn proxy(mut self, url: impl Into<String>) -> Self {
+        self.settings.proxy = Some(url.into());
+        self
+    }
+
+    /// Per-request timeout.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.settings.timeout = Some(timeout);
+        self
+    }
+
+    /// JavDB base URL; shorthand for a single-entry [`ClientBuilder::mirrors`].
+    pub fn javdb_base(self, base: impl Into<String>) -> Self {
+        self.mirrors(Source::JavDb, [base.into()])
+    }
+
+    /// Base URLs of an HTML source in order of preference, replacing the bundled list.
+    /// Requests fail over to the next mirror on connection errors and error statuses.
+    pub fn mirrors<S: Into<String>>(mut self, source: Source, bases: impl IntoIterator<Item = S>) -> Self {
+        let bases = bases.into_iter().map(|b| b.into().trim_end_matches('/').to_string()).collect();
+        self.settings.mirrors.insert(source, bases);
+        self
+    }
+
+    /// Cookie header sent to JavDB (needed for some logged-in only pages).
+    pub fn javdb_cookie(mut self, cookie: impl Into<String>) -> Self {
+        self.settings.javdb_cookie = Some(cookie.into());
+        self
+    }
+
+    pub fn dmm_credentials(mut self, api_id: impl Into<String>, affiliate_id: impl Into<String>) -> Self {
+        self.settings.dmm = Some((api_id.into(), affiliate_id.into()));
+        self
+    }
+
+    /// Skip sources whose circuit breaker is open (the default). Outcomes are
+    /// recorded either way, so a client with this off can probe and close them.
+    pub fn circuit_breaker(mut self, on: bool) -> Self {
+        self.settings.circuit_breaker = on;
+        self
+    }
+
+    /// Languages to fetch titles, plots, genres and cast in, where sources offer them.
+    /// Details are presented in the first; the others end up in [`AvDetail::localized`].
+    pub fn languages(mut self, languages: impl IntoIterator<Item = Lang>) -> Self {
+        let mut list: Vec<Lang> = Vec::new();
+        for lang in languages {
+            if !list.contains(&lang) { list.push(lang); }
+        }
+        if !list.is_empty() { self.settings.languages = list; }
+        self
+    }
+
+    /// Preferred metadata language, keeping any further [`ClientBuilder::languages`] after it.
+    pub fn language(self, lang: Lang) -> Self {
+        let rest = self.settings.languages.iter().skip(1).copied().collect::<Vec<_>>();
+        self.languages(std::iter::once(lang).chain(rest))
+    }
+
+    /// Translate titles and plots the sources lack in the preferred language; see
+    /// [`AvDetail::translated`].
+    pub fn translator(mut self, translator: Translator) -> Self {
+        self.settings.translator = Some(translator);
+        self
+    }
+
+    /// Keep looked-up details in memory for `ttl`.
+    pub fn cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = Some(ttl);
+        self
+    }
+
+    pub fn build(self) -> Result<Client> {
+        if let Some(p) = &self.settings.proxy {
+            reqwest::Proxy::all(p).with_context(|| i18n::tf("err.invalid_proxy", &[p]))?;
+        }
+        Ok(Client {
+            settings: Arc::new(self.settings),
+            cache: self.cache_ttl.map(|ttl| Arc::new(DetailCache { ttl, entries: Mutex::new(HashMap::new()) })),
+        })
+    }
+}
+
+#[derive(Debug)]
+struct DetailCache {
+    ttl: Duration,
+    entries: Mutex<HashMap<String, (Instant, AvDetail)>>,
+}
+
+impl DetailCache {
+    fn get(&self, key: &str) -> Option<AvDetail> {
+        let entries = self.entries.lock().ok()?;
+        entries.get(key).filter(|(at, _)| at.elapsed() < self.ttl).map(|(_, d)| d.clone())
+    }
+
+    fn put(&self, key: String, detail: &AvDetail) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.retain(|_, (at, _)| at.elapsed() < self.ttl);
+            entries.insert(key, (Instant::now(), detail.clone()));
+        }
+    }
+}
+
+/// Entry point of the library: looks up titles, actors and listings across the
+/// configured sources. Cheap to clone; clones share the cache.
+#[derive(Debug, Clone)]
+pub struct Client {
+    settings: Arc<Settings>,
+    cache: Option<Arc<DetailCache>>,
+}
+
+impl Client {
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::default()
+    }
+
+    /// Client configured like the `av` CLI, see [`ClientBuilder::from_env`].
+    pub fn from_env() -> Result<Self> {
+        ClientBuilder::from_env().build()
+    }
+
+    async fn scoped<F: Future>(&self, fut: F) -> F::Output {
+        CURRENT.scope(self.settings.clone(), fut).await
+    }
+
+    /// `d` with the fields derived from the merged ones (censorship, canonical genres
+    /// across every language, clean title), presented in the preferred language.
+    fn finish(&self, mut d: AvDetail) -> AvDetail {
+        d.censorship = classify::detail(&d);
+        let mut tags = d.genres.clone();
+        tags.extend(d.localized.values().flat_map(|v| v.genres.iter().cloned()));
+        d.genre_ids = genres::canonical_ids(&tags);
+        if let Some(lang) = self.settings.languages.first() { d.localize(*lang); }
+        d.original_title = d.title.clone();
+        d.title = title::normalize_width(d.title.trim());
+        let mut names = d.actor_names.clone();
+        names.extend(d.cast.iter().map(|m| m.name.clone()));
+        d.clean_title = title::clean(&d.title, &d.code, &names);
+        d
+    }
+
+    /// [`Client::finish`] plus the translation stage, when a translator is configured.
+    async fn finish_translated(&self, d: AvDetail) -> AvDetail {
+        let mut d = self.finish(d);
+        if let (Some(translator), Some(lang)) = (&self.settings.translator, self.settings.languages.first()) {
+            translate::detail(translator, *lang, self.settings.timeout, &mut d).await;
+        }
+        d
+    }
+
+    /// Full metadata and magnets of `code`. Fails with [`crate::AmbiguousMatch`] when
+    /// several titles carry the code.
+    pub async fn detail(&self, code: &str) -> Result<AvDetail> {
+        self.detail_pick(code, None).await
+    }
+
+    /// Like [`Client::detail`], taking the `pick`-th (1-based) candidate when the code is ambiguous.
+    pub async fn detail_pick(&self, code: &str, pick: Option<usize>) -> Result<AvDetail> {
+        let key = format!("{}#{}", code.to_uppercase(), pick.unwrap_or(0));
+        if let Some(hit) = self.cache.as_ref().and_then(|c| c.get(&key)) {
+            util::debug(format!("cache hit: {}", key));
+            return Ok(hit);
+        }
+        let detail = self.finish_translated(self.scoped(scraper::fetch_detail_pick(code, pick)).await?).await;
+        if let Some(cache) = &self.cache {
+            cache.put(key, &detail);
+        }
+        Ok(detail)
+    }
+
+    /// Like [`Client::detail`] for a title page URL (JavDB, DMM, or any URL containing the code).
+    pub async fn detail_by_url(&self, url: &str) -> Result<AvDetail> {
+        Ok(self.finish_translated(self.scoped(scraper::fetch_detail_by_url(url)).await?).await)
+    }
+
+    /// Titles matching a code or keyword.
+    pub async fn search(&self, query: &str) -> Result<Vec<AvItem>> {
+        self.scoped(scraper::search(query)).await
+    }
+
+    /// Every title of an actor.
+    pub async fn actor_titles(&self, actor: &str) -> Result<Vec<AvItem>> {
+        self.scoped(scraper::list_actor_titles(actor)).await
+    }
+
+    /// Newest releases, at most `limit`.
+    pub async fn latest(&self, limit: usize) -> Result<Vec<AvItem>> {
+        self.scoped(scraper::top(limit)).await
+    }
+
+    /// One page of the actor ranking.
+    pub async fn actors(&self, page: usize, per_page: usize, uncensored_only: bool) -> Result<ActorPage> {
+        let (actors, total) = self.scoped(scraper::actors(page, per_page, uncensored_only)).await?;
+        Ok(ActorPage { actors, total, page, per_page })
+    }
+
+    /// Page `page` (1-based) of the user reviews of `code`, with its rating and breakdown.
+    /// Takes the `pick`-th candidate when the code is ambiguous, like [`Client::detail_pick`].
+    pub async fn reviews(&self, code: &str, page: usize, pick: Option<usize>) -> Result<ReviewPage> {
+        self.scoped(scraper::reviews(code, page.max(1), pick)).await
+    }
+
+    /// Page where the title can be watched, or the search page when none is linked.
+    pub async fn play_url(&self, code: &str) -> Result<String> {
+        self.scoped(scraper::get_play_url(code)).await
+    }
+
+    /// DMM makers/series/genres/floors; requires DMM credentials.
+    pub async fn browse(&self, facet: Facet, initial: Option<&str>, page: usize, per_page: usize) -> Result<Vec<Entry>> {
+        self.scoped(dmm::browse(facet, initial, page, per_page)).await
+    }
+
+    /// Whether lookups query `source`.
+    pub fn uses(&self, source: Source) -> bool {
+        self.settings.has(source)
+    }
+
+    /// Base URLs of an HTML source in configured order; none for DMM.
+    pub fn mirrors(&self, source: Source) -> Vec<String> {
+        self.settings.mirrors(source)
+    }
+
+    /// Detail of `code` from `source` alone: no merging with other sources and no cache.
+    /// `Ok(None)` when the source does not know the code.
+    pub async fn detail_from(&self, source: Source, code: &str) -> Result<Option<AvDetail>> {
+        Ok(self.scoped(scraper::fetch_detail_from(source, code)).await?.map(|d| self.finish(d)))
+    }
+
+    /// Titles of `actor` from `source` alone.
+    pub async fn actor_titles_from(&self, source: Source, actor: &str) -> Result<Vec<AvItem>> {
+        self.scoped(scraper::list_actor_titles_from(source, actor)).await
+    }
+
+    /// HTTP status of `source`'s front page (for DMM, of its API endpoint).
+    pub async fn ping(&self, source: Source) -> Result<u16> {
+        self.scoped(scraper::ping(source)).await
+    }
+
+    /// Cover image of `code`, kept on disk in the cache directory's `covers` folder once downloaded.
+    pub async fn cover(&self, code: &str) -> Result<Image> {
+        // The code names the cached file, so it must not carry a path
+        CodeKey::parse(code).ok_or_else(|| BadRequest::new(i18n::t("err.bad_code")))?;
+        let key = code.to_uppercase();
+        let dir = util::cache_dir().map(|d| d.join("covers"));
+        if let Some(dir) = &dir {
+            for ext in ["jpg", "png", "webp"] {
+                if let Ok(bytes) = std::fs::read(dir.join(format!("{}.{}", key, ext))) {
+                    return Ok(Image { content_type: mime_of(ext).to_string(), bytes });
+                }
+            }
+        }
+        let detail = self.detail(code).await?;
+        let url = detail.cover_url.ok_or_else(|| NotFound::new(i18n::tf("err.no_cover", &[&key])))?;
+        util::debug(format!("cover: downloading {}", url));
+        let http = self.settings.http_builder().build()?;
+        let resp = http.get(&url).send().await?.error_for_status()?;
+        let content_type = resp
+            .headers()
+            .get(reqwest::header::CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or("image/jpeg")
+            .to_string();
+        let bytes = resp.bytes().await?.to_vec();
+        if let Some(dir) = dir {
+            let ext = match content_type.as_str() {
+                "image/png" => "png",
+                "image/webp" => "webp",
+                _ => "jpg",
+            };
+            let written = std::fs::create_dir_all(&dir).and_then(|_| std::fs::write(dir.join(format!("{}.{}", key, ext)), &bytes));
+            if let Err(e) = written { util::debug(format!("cover: cache write failed: {}", e)); }
+        }
+        Ok(Image { content_type, bytes })
+    }
+
+    /// Download the largest official trailer of `code` into `dir` as `<CODE>-trailer.mp4`,
+    /// the name Kodi and Jellyfin pick up next to the video. Returns the written path.
+    pub async fn download_trailer(&self, code: &str, dir: &Path) -> Result<PathBuf> {
+        let detail = self.detail(code).await?;
+        let trailer = detail.trailers.last().ok_or_else(|| NotFound::new(i18n::tf("err.no_trailer", &[&detail.code])))?;
+        let url = self.scoped(dmm::trailer_file(&trailer.url)).await?;
+        util::debug(format!("trailer: downloading {}", url));
+        let http = self.settings.http_builder().build()?;
+        let mut resp = http.get(&url).send().await?.error_for_status()?;
+        let path = dir.join(format!("{}-trailer.mp4", detail.code));
+        // Written under a temporary name so an interrupted download leaves no truncated trailer
+        let part = path.with_extension("mp4.part");
+        tokio::fs::create_dir_all(dir).await.with_context(|| i18n::tf("err.write_file", &[&dir.display()]))?;
+        let write_failed = || i18n::tf("err.write_file", &[&part.display()]);
+        let written = async {
+            let mut file = tokio::fs::File::create(&part).await.with_context(write_failed)?;
+            while let Some(chunk) = resp.chunk().await? {
+                file.write_all(&chunk).await.with_context(write_failed)?;
+            }
+            file.flush().await.with_context(write_failed)
+        };
+        if let Err(e) = written.await {
+            // Nothing to resume from, so drop the partial file
+            let _ = tokio::fs::remove_file(&part).await;
+            return Err(e);
+        }
+        tokio::fs::rename(&part, &path).await.with_context(|| i18n::tf("err.write_file", &[&path.display()]))?;
+        Ok(path)
+    }
+}
+
+fn mime_of(ext: &str) -> &'static str {
+    match ext {
+        "png" => "image/png",
+        "webp" => "image/webp",
+        _ => "image/jpeg",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_send<T: Send>(_: T) {}
+
+    /// Lookups must be spawnable on a multi-threaded runtime (`tokio::spawn`, axum handlers).
+    #[test]
+    fn lookups_are_send() {
+        let client = Client::builder().build().unwrap();
+        assert_send(client.detail("SSIS-001"));
+        assert_send(client.detail_by_url("https://javdb.com/v/abc"));
+        assert_send(client.search("SSIS-001"));
+        assert_send(client.actor_titles("name"));
+        assert_send(client.latest(10));
+        assert_send(client.actors(1, 50, false));
+        assert_send(client.reviews("SSIS-001", 1, None));
+        assert_send(client.play_url("SSIS-001"));
+        assert_send(client.cover("SSIS-001"));
+        assert_send(client.download_trailer("SSIS-001", Path::new(".")));
+        assert_send(client.detail_from(Source::JavDb, "SSIS-001"));
+        assert_send(client.actor_titles_from(Source::JavDb, "name"));
+        assert_send(client.ping(Source::JavDb));
+        assert_send(client.browse(Facet::Makers, None, 1, 50));
+    }
+
+    #[test]
+    fn language_replaces_the_preferred_one_only() {
+        let b = ClientBuilder::default().languages([Lang::ZhHans, Lang::Ja, Lang::En]).language(Lang::En);
+        assert_eq!(b.settings.languages, [Lang::En, Lang::Ja]);
+        assert_eq!(ClientBuilder::default().settings.languages, [Lang::ZhHans]);
+    }
+}
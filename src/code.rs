@@ -0,0 +1,154 @@
+use regex::Regex;
+
+/// Normalized form of a title code, used to compare codes coming from different sources.
+///
+/// `ABC-123`, `abc123` and `ABC_00123` all normalize to the same key, while `ABC-12`
+/// and `ABC-123`, or `3DSVR-001` and `DSVR-001`, stay distinct.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CodeKey {
+    pub prefix: String,
+    pub number: u64,
+    pub suffix: String,
+}
+
+impl CodeKey {
+    pub fn parse(s: &str) -> Option<CodeKey> {
+        let upper = s.trim().to_uppercase();
+        // DMM label prefix: `h_1234abc00123`
+        let upper = Regex::new(r"^H_\d+").unwrap().replace(&upper, "").to_string();
+        let cleaned: String = upper.chars().filter(|c| c.is_ascii_alphanumeric()).collect();
+        let caps = Regex::new(r"^([A-Z0-9]*?)(\d+)([A-Z]*)$").unwrap().captures(&cleaned)?;
+        Some(CodeKey {
+            prefix: caps[1].to_string(),
+            number: caps[2].parse().ok()?,
+            suffix: caps[3].to_string(),
+        })
+    }
+
+    /// Key of a DMM content ID, whose numeric label prefix is dropped (`118abc00123` => `ABC-123`).
+    pub fn from_content_id(cid: &str) -> Option<CodeKey> {
+        let mut key = CodeKey::parse(cid)?;
+        if let Some(at) = key.prefix.find(|c: char| c.is_ascii_alphabetic()) {
+            key.prefix.drain(..at);
+        }
+        Some(key)
+    }
+
+    pub fn matches(&self, other: &str) -> bool {
+        CodeKey::parse(other).as_ref() == Some(self)
+    }
+
+    /// Whether DMM content ID `cid` names this code, as spelled or behind a numeric label
+    /// prefix. The second form cannot tell `13dsvr00001` (`3DSVR-001`) from `DSVR-001`,
+    /// so callers should prefer an exact [`CodeKey::matches`] hit.
+    pub fn matches_content_id(&self, cid: &str) -> bool {
+        self.matches(cid) || CodeKey::from_content_id(cid).as_ref() == Some(self)
+    }
+
+    /// Whether `text` (a torrent name, a card title) mentions exactly this code,
+    /// so `ABC-12` does not match inside `ABC-123` or `XABC-12`.
+    pub fn found_in(&self, text: &str) -> bool {
+        let pattern = format!(r"(?i)(?:^|[^A-Z0-9]){}(?:[^A-Z0-9]|$)", self.pattern());
+        Regex::new(&pattern).map(|re| re.is_match(text)).unwrap_or(false)
+    }
+
+    /// Regex source matching the code in any of its spellings, without boundaries.
+    pub(crate) fn pattern(&self) -> String {
+        let sep = "[-_ ]?";
+        let prefix = self
+            .prefix
+            .chars()
+            .map(|c| regex::escape(&c.to_string()))
+            .collect::<Vec<_>>()
+            .join(sep);
+        format!("{}{}0*{}{}", prefix, sep, self.number, regex::escape(&self.suffix))
+    }
+
+    /// Similarity of a candidate code to this one, from 0.0 (unrelated) to 1.0 (same code).
+    pub fn score(&self, candidate: &str) -> f32 {
+        let Some(other) = CodeKey::parse(candidate) else { return 0.0 };
+        if &other == self {
+            return 1.0;
+        }
+        if other.prefix != self.prefix {
+            return 0.0;
+        }
+        if other.number == self.number {
+            // Same title, different part/edition suffix
+            return 0.8;
+        }
+        let (a, b) = (self.number.to_string(), other.number.to_string());
+        if a.starts_with(&b) || b.starts_with(&a) { 0.4 } else { 0.2 }
+    }
+}
+
+/// First code-looking token (`ABC-123`) in free text such as a file name or URL.
+pub fn extract_code(text: &str) -> Option<String> {
+    let re = Regex::new(r"(?i)(?:^|[^A-Z0-9])([A-Z]{2,6})[-_ ]?(\d{2,5})(?:[^0-9]|$)").unwrap();
+    let caps = re.captures(text)?;
+    Some(format!("{}-{}", caps[1].to_uppercase(), &caps[2]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(s: &str) -> CodeKey {
+        CodeKey::parse(s).unwrap()
+    }
+
+    #[test]
+    fn parse_normalizes_spellings() {
+        assert_eq!(key("ABC-123"), key("abc123"));
+        assert_eq!(key("ABC-123"), key("ABC_00123"));
+        assert_ne!(key("ABC-12"), key("ABC-123"));
+    }
+
+    #[test]
+    fn parse_keeps_leading_digits() {
+        assert_eq!(key("300mium00123"), CodeKey { prefix: "300MIUM".into(), number: 123, suffix: String::new() });
+        assert_ne!(key("3DSVR-001"), key("DSVR-001"));
+        assert!(!key("DSVR-001").matches("3DSVR-001"));
+        assert!(!key("PON-001").matches("1PON-001"));
+        assert_eq!(key("DSVR-001").score("3DSVR-001"), 0.0);
+    }
+
+    #[test]
+    fn content_ids_drop_dmm_label_prefixes() {
+        assert_eq!(key("h_1234abc00123"), key("ABC-123"));
+        assert_eq!(key("H_086ABC-123"), key("ABC-123"));
+        assert_eq!(CodeKey::from_content_id("118abc00123"), Some(key("ABC-123")));
+        assert!(key("ABC-123").matches_content_id("1abc00123"));
+        assert!(key("3DSVR-001").matches_content_id("3dsvr00001"));
+        assert!(!key("ABC-123").matches_content_id("1abc00124"));
+    }
+
+    #[test]
+    fn score_tiers() {
+        let k = key("ABC-123");
+        assert_eq!(k.score("abc00123"), 1.0);
+        assert_eq!(k.score("ABC-123C"), 0.8);
+        assert_eq!(k.score("ABC-12"), 0.4);
+        assert_eq!(k.score("ABC-1234"), 0.4);
+        assert_eq!(k.score("ABC-456"), 0.2);
+        assert_eq!(k.score("XYZ-123"), 0.0);
+        assert_eq!(k.score("no code"), 0.0);
+    }
+
+    #[test]
+    fn found_in_respects_boundaries() {
+        let k = key("SSIS-123");
+        assert!(k.found_in("[HD] SSIS-123 title"));
+        assert!(k.found_in("ssis00123.mp4"));
+        assert!(k.found_in("SSIS_123"));
+        assert!(!k.found_in("SSIS-1234 title"));
+        assert!(!k.found_in("XSSIS-123"));
+        assert!(!k.found_in("SSIS-12"));
+    }
+
+    #[test]
+    fn extract_code_finds_first_code() {
+        assert_eq!(extract_code("[HD] ssis_001.mp4").as_deref(), Some("SSIS-001"));
+        assert_eq!(extract_code("no code here"), None);
+    }
+}
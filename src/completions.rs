@@ -0,0 +1,143 @@
+use clap::{Arg, Command};
+use clap_complete::{generate, Shell};
+
+/// Subcommands (and aliases) whose positional argument is a code / an actor name.
+const CODE_COMMANDS: &[&str] = &["install", "get", "detail", "reviews", "trailer", "view", "see"];
+const ACTOR_COMMANDS: &[&str] = &["list", "ls"];
+
+/// Static completion script from the clap definition, with the positional
+/// arguments of code/actor commands completed from `av __complete`.
+pub fn render(shell: Shell, cmd: &mut Command) -> String {
+    let bin = cmd.get_name().to_string();
+    let hidden: Vec<String> = cmd.get_subcommands().filter(|s| s.is_hide_set()).map(|s| s.get_name().to_string()).collect();
+    let mut buf: Vec<u8> = Vec::new();
+    generate(shell, cmd, bin.clone(), &mut buf);
+    let script = String::from_utf8_lossy(&buf).into_owned();
+    match shell {
+        Shell::Bash => script + &bash_hook(&bin, &valued_options(cmd)),
+        Shell::Zsh => zsh_patch(&script, &bin),
+        Shell::Fish => script + &fish_hook(&bin),
+        Shell::PowerShell => powershell_patch(&script, &bin, &hidden),
+        _ => script,
+    }
+}
+
+/// Spellings of every option that takes a value (`--lang`, `--pick`, `-p`…), on any subcommand.
+fn valued_options(cmd: &Command) -> Vec<String> {
+    fn collect(cmd: &Command, out: &mut Vec<String>) {
+        let valued = cmd.get_arguments().filter(|a: &&Arg| !a.is_positional() && a.get_action().takes_values());
+        for arg in valued {
+            out.extend(arg.get_long_and_visible_aliases().into_iter().flatten().map(|l| format!("--{}", l)));
+            out.extend(arg.get_short_and_visible_aliases().into_iter().flatten().map(|s| format!("-{}", s)));
+        }
+        for sub in cmd.get_subcommands() {
+            collect(sub, out);
+        }
+    }
+    let mut out = Vec::new();
+    collect(cmd, &mut out);
+    out.sort();
+    out.dedup();
+    out
+}
+
+fn bash_hook(bin: &str, valued: &[String]) -> String {
+    // The subcommand is the first word that is neither an option nor an option's value
+    // (`--lang ja`, `--lang=ja`, which bash splits at `=`); codes complete any later
+    // positional, but not the value of an option (`--pick <TAB>`).
+    format!(
+        r#"
+_{bin}_dynamic() {{
+    local cur="${{COMP_WORDS[COMP_CWORD]}}" prev="${{COMP_WORDS[COMP_CWORD-1]}}" sub="" i
+    for ((i = 1; i < COMP_CWORD; i++)); do
+        case "${{COMP_WORDS[i]}}" in
+            {valued})
+                ((i++))
+                [[ "${{COMP_WORDS[i]}}" == "=" ]] && ((i++))
+                ;;
+            -*) ;;
+            *) [[ -z "$sub" ]] && sub="${{COMP_WORDS[i]}}" ;;
+        esac
+    done
+    case "$prev" in
+        {valued}|=) sub="" ;;
+    esac
+    if [[ -n "$sub" && "$cur" != -* ]]; then
+        local kind=""
+        case "$sub" in
+            {codes}) kind=codes ;;
+            {actors}) kind=actors ;;
+        esac
+        if [[ -n "$kind" ]]; then
+            local IFS=$'\n'
+            COMPREPLY=( $(compgen -W "$({bin} __complete "$kind" 2>/dev/null)" -- "$cur") )
+            return 0
+        fi
+    fi
+    _{bin} "$@"
+}}
+complete -F _{bin}_dynamic -o nosort -o bashdefault -o default {bin}
+"#,
+        bin = bin,
+        valued = valued.join("|"),
+        codes = CODE_COMMANDS.join("|"),
+        actors = ACTOR_COMMANDS.join("|"),
+    )
+}
+
+fn zsh_patch(script: &str, bin: &str) -> String {
+    let script = script
+        .replace("':code:_default'", &format!("':code:_{}_history_codes'", bin))
+        .replace("':actor:_default'", &format!("':actor:_{}_history_actors'", bin));
+    let helpers = format!(
+        r#"
+(( $+functions[_{bin}_history_codes] )) ||
+_{bin}_history_codes() {{
+    local -a items
+    items=(${{(f)"$({bin} __complete codes 2>/dev/null)"}})
+    compadd -a items
+}}
+(( $+functions[_{bin}_history_actors] )) ||
+_{bin}_history_actors() {{
+    local -a items
+    items=(${{(f)"$({bin} __complete actors 2>/dev/null)"}})
+    compadd -a items
+}}
+"#,
+        bin = bin
+    );
+    // Helpers must be defined before the trailing `compdef`/dispatch lines run
+    match script.rfind("\nif [ \"$funcstack[1]\" = \"_") {
+        Some(idx) => format!("{}{}{}", &script[..idx], helpers, &script[idx..]),
+        None => script + &helpers,
+    }
+}
+
+fn fish_hook(bin: &str) -> String {
+    format!(
+        "\ncomplete -c {bin} -n \"__fish_seen_subcommand_from {codes}\" -f -a \"({bin} __complete codes)\"\n\
+         complete -c {bin} -n \"__fish_seen_subcommand_from {actors}\" -f -a \"({bin} __complete actors)\"\n",
+        bin = bin,
+        codes = CODE_COMMANDS.join(" "),
+        actors = ACTOR_COMMANDS.join(" "),
+    )
+}
+
+fn powershell_patch(script: &str, bin: &str, hidden: &[String]) -> String {
+    // The PowerShell generator offers hidden subcommands (`__complete`) like any other
+    let offers_hidden = |line: &str| hidden.iter().any(|h| line.contains(&format!("[CompletionResult]::new('{0}', '{0}',", h)));
+    let mut out = script.lines().filter(|l| !offers_hidden(l)).map(|l| format!("{}\n", l)).collect::<String>();
+    for (commands, kind) in [(CODE_COMMANDS, "codes"), (ACTOR_COMMANDS, "actors")] {
+        for sub in commands {
+            let case = format!("'{};{}' {{\n", bin, sub);
+            let inject = format!(
+                "{case}            {bin} __complete {kind} | ForEach-Object {{ [CompletionResult]::new($_, $_, [CompletionResultType]::ParameterValue, $_) }}\n",
+                case = case,
+                bin = bin,
+                kind = kind
+            );
+            out = out.replace(&case, &inject);
+        }
+    }
+    out
+}
@@ -0,0 +1,37 @@
+use serde::Deserialize;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::util;
+
+/// `config.toml` in the config directory. Every key is optional; CLI flags and
+/// environment variables take precedence over values here.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Output language (`en`, `ja`, `zh-hans`, `zh-hant`)
+    pub lang: Option<String>,
+}
+
+static CONFIG: OnceLock<Config> = OnceLock::new();
+
+pub fn config_path() -> Option<PathBuf> {
+    util::config_dir().map(|d| d.join("config.toml"))
+}
+
+fn load() -> Config {
+    let Some(path) = config_path() else { return Config::default() };
+    let Ok(text) = fs::read_to_string(&path) else { return Config::default() };
+    match toml::from_str(&text) {
+        Ok(cfg) => cfg,
+        Err(e) => {
+            eprintln!("{}: {}", path.display(), e);
+            Config::default()
+        }
+    }
+}
+
+pub fn get() -> &'static Config {
+    CONFIG.get_or_init(load)
+}
@@ -0,0 +1,219 @@
+use colored::*;
+use std::io::Write;
+
+use av::{genres, i18n, Censorship};
+use av::sources::dmm::Entry;
+use av::types::{ActorItem, AvDetail, AvItem, Candidate, CastRole, RelatedItem, Relation, ReviewPage};
+
+use crate::table::{Column, Table};
+
+pub fn print_items_table(items: &[AvItem]) {
+    println!("{} {}", i18n::t("label.total").bold(), items.len());
+    let mut t = Table::new(vec![
+        Column::new("index", "#").right(),
+        Column::new("code", i18n::t("label.code")),
+        Column::new("title", i18n::t("label.title")).flex(),
+    ]);
+    for (idx, item) in items.iter().enumerate() {
+        t.row(vec![(idx + 1).to_string(), item.code.clone(), item.title.trim().to_string()]);
+    }
+    t.print();
+}
+
+pub fn print_detail_human(detail: &AvDetail) {
+    let mut t = Table::key_value(i18n::t("label.field"), i18n::t("label.value"));
+    let mut field = |key: &str, value: String| t.row(vec![i18n::label(key), value]);
+    field("label.code", detail.code.clone());
+    field("label.title", detail.title.clone());
+    if !detail.clean_title.is_empty() && detail.clean_title != detail.title {
+        field("label.clean_title", detail.clean_title.clone());
+    }
+    if let Some(title) = detail.translated.as_ref().and_then(|t| t.title.clone()) {
+        field("label.translated", title);
+    }
+    if !detail.actor_names.is_empty() {
+        field("label.actors", detail.actor_names.join(", "));
+    }
+    let male: Vec<&str> = detail.cast.iter().filter(|m| m.role == CastRole::Actor).map(|m| m.name.as_str()).collect();
+    if !male.is_empty() {
+        field("label.male_actors", male.join(", "));
+    }
+    if let Some(date) = detail.release_date {
+        field("label.release", date.to_string());
+    }
+    if let Some(cover) = &detail.cover_url {
+        field("label.cover", cover.clone());
+    }
+    if let Some(duration) = detail.duration {
+        field("label.duration", i18n::tf("fmt.minutes", &[&duration.minutes()]));
+    }
+    if let Some(dir) = &detail.director {
+        field("label.director", dir.clone());
+    }
+    if let Some(studio) = &detail.studio {
+        field("label.studio", studio.clone());
+    }
+    if let Some(label) = &detail.label {
+        field("label.label", label.clone());
+    }
+    if let Some(series) = &detail.series {
+        field("label.series", series.clone());
+    }
+    if !detail.genres.is_empty() {
+        field("label.genres", genres::labels(&detail.genres).join(", "));
+    }
+    if let Some(r) = detail.rating {
+        field("label.rating", r.to_string());
+    }
+    if let Some(key) = censorship_key(detail.censorship.category) {
+        let pct = (detail.censorship.confidence * 100.0).round();
+        field("label.censorship", format!("{} ({}%)", i18n::t(key), pct));
+    }
+    if let Some(plot) = &detail.plot {
+        field("label.plot", plot.clone());
+    }
+    if let Some(plot) = detail.translated.as_ref().and_then(|t| t.plot.clone()) {
+        field("label.translated", plot);
+    }
+    if !detail.preview_images.is_empty() {
+        let list = detail.preview_images.iter().enumerate().map(|(i, url)| format!("{}. {}", i + 1, url));
+        field("label.previews", list.collect::<Vec<_>>().join("\n"));
+    }
+    if let Some(t) = detail.trailers.last() {
+        field("label.trailer", format!("{}x{} {}", t.width, t.height, t.url));
+    }
+    if !detail.magnets.is_empty() {
+        let mut lines = vec![i18n::tf("fmt.count", &[&detail.magnets.len()])];
+        for (i, m) in detail.magnet_infos.iter().take(5).enumerate() {
+            lines.push(format!("{}. {}", i + 1, m.url));
+            let mut info: Vec<String> = Vec::new();
+            if let Some(name) = &m.name { info.push(name.clone()); }
+            if let Some(size) = &m.size { info.push(size.clone()); }
+            if let Some(res) = &m.resolution { info.push(res.clone()); }
+            if let Some(codec) = &m.codec { info.push(codec.clone()); }
+            if let Some(b) = m.avg_bitrate_mbps { info.push(format!("~{:.2} Mbps", b)); }
+            let mut peers: Vec<String> = Vec::new();
+            if let Some(s) = m.seeders { peers.push(format!("S:{}", s)); }
+            if let Some(lc) = m.leechers { peers.push(format!("L:{}", lc)); }
+            if !peers.is_empty() { info.push(peers.join(" ")); }
+            if !info.is_empty() { lines.push(format!("   {}", info.join(" | "))); }
+        }
+        field("label.magnets", lines.join("\n"));
+    }
+    t.print();
+}
+
+pub fn print_actors_table(actors: &[ActorItem], page: usize, per_page: usize, total: usize) {
+    println!("{} {} {}", i18n::t("label.total").bold(), total, i18n::tf("fmt.page", &[&page, &total.div_ceil(per_page)]));
+    let mut t = Table::new(vec![
+        Column::new("index", "#").right(),
+        Column::new("name", i18n::t("label.actor")).flex(),
+        Column::new("hot", i18n::t("label.hot")).right(),
+    ]);
+    for (i, a) in actors.iter().enumerate() {
+        let hot = if a.hot == 0 { "-".to_string() } else { a.hot.to_string() };
+        t.row(vec![(i + 1 + (page - 1) * per_page).to_string(), a.name.clone(), hot]);
+    }
+    t.print();
+}
+
+pub fn print_entries_table(entries: &[Entry]) {
+    println!("{} {}", i18n::t("label.total").bold(), entries.len());
+    let mut t = Table::new(vec![
+        Column::new("id", "ID"),
+        Column::new("name", i18n::t("label.name")).flex(),
+        Column::new("ruby", i18n::t("label.ruby")).flex(),
+    ]);
+    for e in entries {
+        t.row(vec![e.id.clone(), e.name.clone(), e.ruby.clone().unwrap_or_default()]);
+    }
+    t.print();
+}
+
+pub fn print_related_table(related: &[RelatedItem]) {
+    println!("{} {}", i18n::t("label.related").bold(), related.len());
+    let mut t = Table::new(vec![
+        Column::new("relation", i18n::t("label.relation")),
+        Column::new("code", i18n::t("label.code")),
+        Column::new("title", i18n::t("label.title")).flex(),
+    ]);
+    for r in related {
+        t.row(vec![i18n::t(relation_key(r.relation)), r.item.code.clone(), r.item.title.trim().to_string()]);
+    }
+    t.print();
+}
+
+pub fn print_reviews(page: &ReviewPage) {
+    if let Some(r) = page.rating {
+        println!("{} {}", i18n::t("label.rating").bold(), r);
+    }
+    for (stars, votes) in page.score_distribution.iter().rev() {
+        println!("  {} {}", "★".repeat(*stars as usize), votes);
+    }
+    println!("{} {} {}", i18n::t("label.reviews").bold(), page.reviews.len(), i18n::tf("fmt.page_only", &[&page.page]));
+    let mut t = Table::new(vec![
+        Column::new("source", i18n::t("label.source")),
+        Column::new("date", i18n::t("label.date")),
+        Column::new("score", i18n::t("label.score")).right(),
+        Column::new("author", i18n::t("label.author")),
+        Column::new("text", i18n::t("label.review")).flex(),
+    ]);
+    for r in &page.reviews {
+        t.row(vec![
+            r.source.clone(),
+            r.date.map(|d| d.to_string()).unwrap_or_default(),
+            r.score.map(|s| s.to_string()).unwrap_or_default(),
+            r.author.clone().unwrap_or_default(),
+            r.text.clone(),
+        ]);
+    }
+    t.print();
+}
+
+pub fn print_candidates_table(candidates: &[Candidate]) {
+    println!("{}", i18n::t("pick.header").yellow().bold());
+    let mut t = Table::new(vec![
+        Column::new("index", "#").right(),
+        Column::new("match", i18n::t("label.match")),
+        Column::new("code", i18n::t("label.code")),
+        Column::new("title", i18n::t("label.title")).flex(),
+        Column::new("source", i18n::t("label.source")),
+    ]);
+    for (i, c) in candidates.iter().enumerate() {
+        let mark = if c.score >= 1.0 { "=" } else { "~" };
+        t.row(vec![(i + 1).to_string(), mark.to_string(), c.code.clone(), c.title.clone(), c.source.clone()]);
+    }
+    t.print();
+}
+
+pub fn is_interactive() -> bool {
+    use std::io::IsTerminal;
+    std::io::stdin().is_terminal() && std::io::stdout().is_terminal()
+}
+
+/// Ask for a 1-based index; empty input or anything out of range cancels.
+pub fn prompt_pick(count: usize) -> Option<usize> {
+    print!("{}", i18n::tf("pick.prompt", &[&count]));
+    std::io::stdout().flush().ok()?;
+    let mut line = String::new();
+    std::io::stdin().read_line(&mut line).ok()?;
+    line.trim().parse::<usize>().ok().filter(|n| (1..=count).contains(n))
+}
+
+pub(crate) fn relation_key(relation: Relation) -> &'static str {
+    match relation {
+        Relation::Similar => "relation.similar",
+        Relation::AlsoViewed => "relation.also_viewed",
+        Relation::SameSeries => "relation.same_series",
+        _ => "relation.other",
+    }
+}
+
+pub(crate) fn censorship_key(category: Censorship) -> Option<&'static str> {
+    match category {
+        Censorship::Censored => Some("censorship.censored"),
+        Censorship::Uncensored => Some("censorship.uncensored"),
+        Censorship::Leaked => Some("censorship.leaked"),
+        _ => None,
+    }
+}
@@ -0,0 +1,254 @@
+use anyhow::Result;
+use schemars::JsonSchema;
+use serde::Serialize;
+use std::time::{Duration, Instant};
+
+use av::sources::defs;
+use av::{breaker, config, i18n, AvDetail, Client, ClientBuilder, Source};
+
+use crate::output::{self, Kind};
+use crate::table::{Column, Table};
+
+/// Per-request timeout, so a hung site fails the check instead of the cron job.
+const TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A title (and actor) every source is known to carry, with the fields its parser must fill.
+struct Canary {
+    source: Source,
+    code: &'static str,
+    actor: Option<&'static str>,
+    fields: &'static [&'static str],
+}
+
+const CANARIES: &[Canary] = &[
+    Canary {
+        source: Source::Dmm,
+        code: "SSIS-001",
+        actor: Some("三上悠亜"),
+        fields: &["title", "actor_names", "release_date", "cover_url", "duration", "studio", "genres"],
+    },
+    Canary {
+        source: Source::JavDb,
+        code: "SSIS-001",
+        actor: Some("三上悠亜"),
+        fields: &["title", "actor_names", "release_date", "cover_url", "duration", "studio", "genres", "preview_images"],
+    },
+    Canary {
+        source: Source::JavLibrary,
+        code: "SSIS-001",
+        actor: None,
+        fields: &["title", "actor_names", "release_date", "cover_url", "studio", "genres"],
+    },
+    Canary {
+        source: Source::Sukebei,
+        code: "SSIS-001",
+        actor: None,
+        fields: &["title", "magnets"],
+    },
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+enum Status {
+    Ok,
+    Fail,
+    Skip,
+}
+
+impl Status {
+    fn label(self) -> String {
+        match self {
+            Status::Ok => i18n::t("doctor.ok"),
+            Status::Fail => i18n::t("doctor.fail"),
+            Status::Skip => i18n::t("doctor.skip"),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Check {
+    check: String,
+    status: Status,
+    http: Option<u16>,
+    latency_ms: Option<u64>,
+    detail: String,
+}
+
+impl Check {
+    fn new(check: impl Into<String>, status: Status, detail: impl Into<String>) -> Self {
+        Self { check: check.into(), status, http: None, latency_ms: None, detail: detail.into() }
+    }
+}
+
+/// `av doctor`: verify the configuration, then run every source against its canary.
+/// Fails (exit code 1) when any check fails, so it can run from cron.
+pub async fn run() -> Result<()> {
+    let mut checks = vec![check_config()];
+    checks.extend(defs::BUNDLED.iter().map(|(name, _)| check_definition(name)));
+    // Probe every source even when its breaker is open; a passing probe closes it
+    let client = ClientBuilder::from_env().language(i18n::lang()).timeout(TIMEOUT).circuit_breaker(false).build();
+    checks.push(check_proxy(client.as_ref().err()));
+    if let Ok(client) = &client {
+        checks.push(check_dmm(client).await);
+        for canary in CANARIES {
+            checks.push(check_source(client, canary).await);
+        }
+    }
+    if output::is_structured() {
+        output::print(Kind::Checks, &checks)?;
+    } else {
+        print_table(&checks);
+    }
+    let failed = checks.iter().filter(|c| c.status == Status::Fail).count();
+    if failed > 0 {
+        anyhow::bail!(i18n::tf("doctor.failed", &[&failed]));
+    }
+    Ok(())
+}
+
+fn check_config() -> Check {
+    let Some(path) = config::config_path() else { return Check::new("config", Status::Fail, i18n::t("err.config_dir")) };
+    match std::fs::read_to_string(&path) {
+        Err(_) => Check::new("config", Status::Ok, i18n::tf("doctor.config_missing", &[&path.display()])),
+        Ok(text) => match toml::from_str::<config::Config>(&text) {
+            Ok(_) => Check::new("config", Status::Ok, path.display().to_string()),
+            Err(e) => Check::new("config", Status::Fail, format!("{}: {}", path.display(), e)),
+        },
+    }
+}
+
+/// Override of a bundled source definition: parses and targets this schema version.
+fn check_definition(name: &str) -> Check {
+    let check = format!("defs:{}", name);
+    let Some(path) = defs::override_path(name) else { return Check::new(check, Status::Ok, i18n::t("doctor.bundled")) };
+    let Ok(text) = std::fs::read_to_string(&path) else { return Check::new(check, Status::Ok, i18n::t("doctor.bundled")) };
+    match toml::from_str::<defs::SourceDef>(&text) {
+        Ok(def) if def.version != defs::SCHEMA_VERSION => Check::new(
+            check,
+            Status::Fail,
+            i18n::tf("defs.bad_version", &[&path.display(), &def.version, &defs::SCHEMA_VERSION]),
+        ),
+        Ok(_) => Check::new(check, Status::Ok, i18n::tf("doctor.override", &[&path.display()])),
+        Err(e) => Check::new(check, Status::Fail, format!("{}: {}", path.display(), e)),
+    }
+}
+
+fn check_proxy(build_error: Option<&anyhow::Error>) -> Check {
+    if let Some(e) = build_error {
+        return Check::new("proxy", Status::Fail, format!("{:#}", e));
+    }
+    match std::env::var("AV_HTTP_PROXY").ok().filter(|p| !p.is_empty()) {
+        Some(proxy) => Check::new("proxy", Status::Ok, proxy),
+        None => Check::new("proxy", Status::Skip, i18n::t("doctor.proxy_none")),
+    }
+}
+
+/// DMM is opt-in: only its credentials are checked here, the canary runs with the other sources.
+async fn check_dmm(client: &Client) -> Check {
+    let wanted = std::env::var("AV_USE_DMM").as_deref() == Ok("1");
+    if !wanted {
+        return Check::new("dmm:credentials", Status::Skip, i18n::t("doctor.dmm_off"));
+    }
+    if !client.uses(Source::Dmm) {
+        return Check::new("dmm:credentials", Status::Fail, i18n::t("err.dmm_unconfigured"));
+    }
+    match client.browse(av::sources::dmm::Facet::Floors, None, 1, 1).await {
+        Ok(_) => Check::new("dmm:credentials", Status::Ok, i18n::t("doctor.dmm_ok")),
+        Err(e) => Check::new("dmm:credentials", Status::Fail, format!("{:#}", e)),
+    }
+}
+
+async fn check_source(client: &Client, canary: &Canary) -> Check {
+    let name = canary.source.name();
+    if !client.uses(canary.source) {
+        return Check::new(name, Status::Skip, i18n::t("doctor.disabled"));
+    }
+    let mut check = Check::new(name, Status::Ok, String::new());
+    let mut notes: Vec<String> = Vec::new();
+    if let Some(left) = breaker::retry_in(canary.source) {
+        notes.push(i18n::tf("doctor.circuit_open", &[&left.as_secs().div_ceil(60)]));
+    }
+    let started = Instant::now();
+    match client.ping(canary.source).await {
+        Ok(status) => {
+            check.http = Some(status);
+            if status >= 400 { check.status = Status::Fail; }
+        }
+        Err(e) => {
+            check.status = Status::Fail;
+            notes.push(format!("{:#}", e));
+        }
+    }
+    check.latency_ms = Some(started.elapsed().as_millis() as u64);
+
+    match client.detail_from(canary.source, canary.code).await {
+        Ok(Some(detail)) => {
+            let missing = missing_fields(&detail, canary.fields);
+            if missing.is_empty() {
+                notes.push(i18n::tf("doctor.canary_ok", &[&canary.code]));
+            } else {
+                check.status = Status::Fail;
+                notes.push(i18n::tf("doctor.missing", &[&canary.code, &missing.join(", ")]));
+            }
+        }
+        Ok(None) => {
+            check.status = Status::Fail;
+            notes.push(i18n::tf("doctor.not_found", &[&canary.code]));
+        }
+        Err(e) => {
+            check.status = Status::Fail;
+            notes.push(format!("{}: {:#}", canary.code, e));
+        }
+    }
+
+    if let Some(actor) = canary.actor {
+        match client.actor_titles_from(canary.source, actor).await {
+            Ok(items) if !items.is_empty() => notes.push(i18n::tf("doctor.actor_ok", &[&actor, &items.len()])),
+            Ok(_) => {
+                check.status = Status::Fail;
+                notes.push(i18n::tf("doctor.actor_empty", &[&actor]));
+            }
+            Err(e) => {
+                check.status = Status::Fail;
+                notes.push(format!("{}: {:#}", actor, e));
+            }
+        }
+    }
+    check.detail = notes.join("; ");
+    check
+}
+
+/// Expected fields that came back null or empty.
+fn missing_fields(detail: &AvDetail, fields: &[&str]) -> Vec<String> {
+    let value = serde_json::to_value(detail).unwrap_or_default();
+    fields
+        .iter()
+        .filter(|f| match value.get(**f) {
+            None | Some(serde_json::Value::Null) => true,
+            Some(serde_json::Value::String(s)) => s.trim().is_empty(),
+            Some(serde_json::Value::Array(a)) => a.is_empty(),
+            Some(_) => false,
+        })
+        .map(|f| f.to_string())
+        .collect()
+}
+
+fn print_table(checks: &[Check]) {
+    let mut t = Table::new(vec![
+        Column::new("check", i18n::t("label.check")),
+        Column::new("status", i18n::t("label.status")),
+        Column::new("http", "HTTP").right(),
+        Column::new("latency", i18n::t("label.latency")).right(),
+        Column::new("detail", i18n::t("label.detail")).flex(),
+    ]);
+    for c in checks {
+        t.row(vec![
+            c.check.clone(),
+            c.status.label(),
+            c.http.map(|s| s.to_string()).unwrap_or_default(),
+            c.latency_ms.map(|ms| format!("{} ms", ms)).unwrap_or_default(),
+            c.detail.clone(),
+        ]);
+    }
+    t.print();
+}
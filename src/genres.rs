@@ -0,0 +1,133 @@
+//! Cross-source genre taxonomy. Sources tag titles in their own language (JavDB
+//! in Chinese, DMM in Japanese, JavLibrary in English); `assets/genres.toml` maps
+//! each known tag to a canonical ID with localized names. `<config dir>/genres.toml`
+//! adds genres or replaces bundled ones by ID.
+
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::i18n::{self, Lang};
+use crate::util;
+
+/// Schema version this build understands; overrides with another `version` are ignored.
+pub const SCHEMA_VERSION: u32 = 1;
+
+const BUNDLED: &str = include_str!("../assets/genres.toml");
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Genre {
+    #[serde(skip)]
+    pub id: String,
+    /// In catalogue order: en, ja, zh-Hans, zh-Hant
+    pub names: [String; 4],
+    #[serde(default)]
+    pub tags: Vec<String>,
+}
+
+impl Genre {
+    /// Name in the output language.
+    pub fn name(&self) -> &str {
+        let i = match i18n::lang() {
+            Lang::En => 0,
+            Lang::Ja => 1,
+            Lang::ZhHans => 2,
+            Lang::ZhHant => 3,
+        };
+        &self.names[i]
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct Taxonomy {
+    version: u32,
+    genres: BTreeMap<String, Genre>,
+}
+
+struct Index {
+    genres: Vec<Genre>,
+    /// Normalized ID, name or tag to position in `genres`
+    by_key: HashMap<String, usize>,
+}
+
+/// Case and whitespace do not distinguish tags.
+fn key(text: &str) -> String {
+    text.chars().filter(|c| !c.is_whitespace()).flat_map(char::to_lowercase).collect()
+}
+
+/// Where the taxonomy override is looked up.
+pub fn override_path() -> Option<PathBuf> {
+    util::config_dir().map(|d| d.join("genres.toml"))
+}
+
+fn load() -> Index {
+    let mut taxonomy: Taxonomy = toml::from_str(BUNDLED).expect("bundled genre taxonomy");
+    if let Some(path) = override_path() {
+        if let Ok(text) = std::fs::read_to_string(&path) {
+            let origin = path.display().to_string();
+            match toml::from_str::<Taxonomy>(&text) {
+                Ok(over) if over.version != SCHEMA_VERSION => {
+                    eprintln!("{}", i18n::tf("defs.bad_version", &[&origin, &over.version, &SCHEMA_VERSION]));
+                }
+                Ok(over) => {
+                    util::debug(format!("genre taxonomy override: {}", origin));
+                    taxonomy.genres.extend(over.genres);
+                }
+                Err(e) => eprintln!("{}: {}", origin, e),
+            }
+        }
+    }
+    let mut genres = Vec::new();
+    let mut by_key = HashMap::new();
+    for (id, mut genre) in taxonomy.genres {
+        genre.id = id;
+        let i = genres.len();
+        for k in std::iter::once(&genre.id).chain(&genre.names).chain(&genre.tags) {
+            by_key.entry(key(k)).or_insert(i);
+        }
+        genres.push(genre);
+    }
+    Index { genres, by_key }
+}
+
+fn index() -> &'static Index {
+    static INDEX: OnceLock<Index> = OnceLock::new();
+    INDEX.get_or_init(load)
+}
+
+/// Every canonical genre, by ID.
+pub fn all() -> &'static [Genre] {
+    &index().genres
+}
+
+/// Genre with canonical `id`.
+pub fn get(id: &str) -> Option<&'static Genre> {
+    all().iter().find(|g| g.id == id)
+}
+
+/// Genre a source tag, localized name or ID stands for.
+pub fn lookup(tag: &str) -> Option<&'static Genre> {
+    let index = index();
+    index.by_key.get(&key(tag)).map(|&i| &index.genres[i])
+}
+
+/// Canonical IDs of `tags`, without duplicates, in order of first appearance.
+pub fn canonical_ids(tags: &[String]) -> Vec<String> {
+    let mut ids: Vec<String> = Vec::new();
+    for g in tags.iter().filter_map(|t| lookup(t)) {
+        if !ids.contains(&g.id) { ids.push(g.id.clone()); }
+    }
+    ids
+}
+
+/// `tags` for display: known ones by their localized name, the rest as given.
+pub fn labels(tags: &[String]) -> Vec<String> {
+    let mut out: Vec<String> = Vec::new();
+    for t in tags {
+        let label = lookup(t).map_or_else(|| t.clone(), |g| g.name().to_string());
+        if !out.contains(&label) { out.push(label); }
+    }
+    out
+}
@@ -0,0 +1,61 @@
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+
+use av::{i18n, util};
+
+/// Most-recently-used entries kept per list.
+const MAX_ENTRIES: usize = 200;
+
+/// Codes and actor names previously looked up, used for shell completion.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct History {
+    #[serde(default)]
+    pub codes: Vec<String>,
+    #[serde(default)]
+    pub actors: Vec<String>,
+}
+
+fn history_path() -> Option<PathBuf> {
+    util::config_dir().map(|d| d.join("history.json"))
+}
+
+pub fn load() -> History {
+    history_path()
+        .and_then(|p| fs::read_to_string(p).ok())
+        .and_then(|s| serde_json::from_str(&s).ok())
+        .unwrap_or_default()
+}
+
+fn save(history: &History) -> Result<()> {
+    let path = history_path().with_context(|| i18n::t("err.config_dir"))?;
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir).with_context(|| i18n::t("err.create_config_dir"))?;
+    }
+    fs::write(&path, serde_json::to_string_pretty(history)?).with_context(|| i18n::t("err.write_history"))?;
+    Ok(())
+}
+
+fn push_front(list: &mut Vec<String>, value: &str) {
+    let value = value.trim();
+    if value.is_empty() { return; }
+    list.retain(|v| v != value);
+    list.insert(0, value.to_string());
+    list.truncate(MAX_ENTRIES);
+}
+
+/// Record a looked-up code and, optionally, the actors it resolved to.
+/// History is a convenience, so failures only show up in `--debug` output.
+pub fn record(codes: &[&str], actors: &[String]) {
+    let mut history = load();
+    for c in codes {
+        push_front(&mut history.codes, &c.to_uppercase());
+    }
+    for a in actors {
+        push_front(&mut history.actors, a);
+    }
+    if let Err(e) = save(&history) {
+        util::debug(format!("history: {:#}", e));
+    }
+}
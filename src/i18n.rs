@@ -0,0 +1,322 @@
+use std::fmt::Display;
+use std::sync::OnceLock;
+
+use crate::config;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Lang {
+    En,
+    Ja,
+    ZhHans,
+    ZhHant,
+}
+
+impl Lang {
+    /// Accepts `--lang` values as well as POSIX locales (`ja_JP.UTF-8`, `zh_TW`, `zh-Hant-HK`).
+    pub fn from_tag(tag: &str) -> Option<Lang> {
+        let tag = tag.split('.').next().unwrap_or("").replace('_', "-").to_lowercase();
+        let mut parts = tag.split('-');
+        match parts.next()? {
+            "en" => Some(Lang::En),
+            "ja" | "jp" => Some(Lang::Ja),
+            "zh" => {
+                let rest: Vec<&str> = parts.collect();
+                let traditional = rest.iter().any(|p| matches!(*p, "hant" | "tw" | "hk" | "mo"));
+                Some(if traditional { Lang::ZhHant } else { Lang::ZhHans })
+            }
+            _ => None,
+        }
+    }
+
+    /// BCP 47 tag: `en`, `ja`, `zh-Hans` or `zh-Hant`.
+    pub fn tag(self) -> &'static str {
+        match self {
+            Lang::En => "en",
+            Lang::Ja => "ja",
+            Lang::ZhHans => "zh-Hans",
+            Lang::ZhHant => "zh-Hant",
+        }
+    }
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+static LANG: OnceLock<Lang> = OnceLock::new();
+
+/// Pick the language: `--lang`, then `lang` in config.toml, then `LC_ALL`/`LC_MESSAGES`/`LANG`
+/// (skipping the `C`/`POSIX` locales scripts set). Without any of them output stays in
+/// Simplified Chinese.
+pub fn init(cli_lang: Option<&str>) {
+    let from_env = || {
+        let neutral = |v: &str| matches!(v.split(['.', '@']).next(), Some("C" | "POSIX"));
+        ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .iter()
+            .filter_map(|k| std::env::var(k).ok())
+            .find(|v| !v.is_empty() && !neutral(v))
+            .and_then(|v| Lang::from_tag(&v))
+    };
+    let lang = cli_lang
+        .and_then(Lang::from_tag)
+        .or_else(|| config::get().lang.as_deref().and_then(Lang::from_tag))
+        .or_else(from_env)
+        .unwrap_or(Lang::ZhHans);
+    let _ = LANG.set(lang);
+}
+
+pub fn lang() -> Lang {
+    *LANG.get().unwrap_or(&Lang::ZhHans)
+}
+
+/// `--lang` has to be known before clap builds the (localized) help text.
+pub fn lang_arg_from_env() -> Option<String> {
+    let mut args = std::env::args().skip(1);
+    while let Some(a) = args.next() {
+        if a == "--" { break; }
+        if a == "--lang" { return args.next(); }
+        if let Some(v) = a.strip_prefix("--lang=") { return Some(v.to_string()); }
+    }
+    None
+}
+
+/// Catalogue entry for `key` in the current language.
+pub fn lookup(key: &str) -> Option<&'static str> {
+    CATALOG.iter().find(|(k, _)| *k == key).map(|(_, v)| v[lang().index()])
+}
+
+/// Message for `key` in the current language; unknown keys come back verbatim.
+pub fn t(key: &str) -> String {
+    lookup(key).map(str::to_string).unwrap_or_else(|| key.to_string())
+}
+
+/// `t` with `{}` placeholders filled in order.
+pub fn tf(key: &str, args: &[&dyn Display]) -> String {
+    let template = t(key);
+    let mut out = String::with_capacity(template.len());
+    let mut args = args.iter();
+    let mut rest = template.as_str();
+    while let Some(idx) = rest.find("{}") {
+        out.push_str(&rest[..idx]);
+        match args.next() {
+            Some(a) => out.push_str(&a.to_string()),
+            None => out.push_str("{}"),
+        }
+        rest = &rest[idx + 2..];
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Field label followed by the language's colon, e.g. `番号：` / `Code: `.
+pub fn label(key: &str) -> String {
+    format!("{}{}", t(key), t("punct.colon"))
+}
+
+/// key => [en, ja, zh-Hans, zh-Hant]
+static CATALOG: &[(&str, [&str; 4])] = &[
+    ("punct.colon", [": ", "：", "：", "："]),
+    // Field labels and table headers
+    ("label.code", ["Code", "品番", "番号", "番號"]),
+    ("label.title", ["Title", "タイトル", "标题", "標題"]),
+    ("label.actors", ["Actors", "出演者", "演员", "演員"]),
+    ("label.actor", ["Actor", "女優", "演员", "演員"]),
+    ("label.release", ["Released", "発売日", "发行", "發行"]),
+    ("label.cover", ["Cover", "ジャケット", "封面", "封面"]),
+    ("label.duration", ["Duration", "収録時間", "时长", "時長"]),
+    ("label.director", ["Director", "監督", "导演", "導演"]),
+    ("label.studio", ["Studio", "メーカー", "片商", "片商"]),
+    ("label.label", ["Label", "レーベル", "厂牌", "廠牌"]),
+    ("label.series", ["Series", "シリーズ", "系列", "系列"]),
+    ("label.genres", ["Genres", "ジャンル", "类别", "類別"]),
+    ("label.rating", ["Rating", "評価", "评分", "評分"]),
+    ("label.censorship", ["Mosaic", "モザイク", "有码/无码", "有碼/無碼"]),
+    ("censorship.censored", ["Censored", "モザイクあり", "有码", "有碼"]),
+    ("censorship.uncensored", ["Uncensored", "無修正", "无码", "無碼"]),
+    ("censorship.leaked", ["Leaked", "流出", "流出", "流出"]),
+    ("label.reviews", ["Reviews", "レビュー", "评论", "評論"]),
+    ("label.review", ["Review", "レビュー", "评论", "評論"]),
+    ("label.author", ["Author", "投稿者", "作者", "作者"]),
+    ("label.score", ["Score", "評価", "评分", "評分"]),
+    ("label.date", ["Date", "日付", "日期", "日期"]),
+    ("label.related", ["Related titles", "関連作品", "相关作品", "相關作品"]),
+    ("label.relation", ["Relation", "関連", "关系", "關係"]),
+    ("relation.similar", ["Similar", "おすすめ", "可能也喜欢", "可能也喜歡"]),
+    ("relation.also_viewed", ["Also viewed", "この作品を見た人はこちらも", "看过的人也看过", "看過的人也看過"]),
+    ("relation.same_series", ["Same series", "同シリーズ", "同系列", "同系列"]),
+    ("relation.other", ["Other", "その他", "其他", "其他"]),
+    ("label.male_actors", ["Male actors", "男優", "男优", "男優"]),
+    ("label.clean_title", ["Clean title", "タイトル（整形）", "精简标题", "精簡標題"]),
+    ("label.translated", ["Translation", "翻訳", "译文", "譯文"]),
+    ("label.plot", ["Plot", "あらすじ", "剧情", "劇情"]),
+    ("label.trailer", ["Trailer", "サンプル動画", "预告片", "預告片"]),
+    ("label.previews", ["Previews", "サンプル画像", "预览图", "預覽圖"]),
+    ("label.samples", ["Sample images", "サンプル画像（大）", "大图", "大圖"]),
+    ("label.magnets", ["Magnets", "マグネット", "磁力", "磁力"]),
+    ("label.hot", ["Hot", "人気", "热度", "熱度"]),
+    ("label.name", ["Name", "名前", "名称", "名稱"]),
+    ("label.ruby", ["Reading", "読み", "读音", "讀音"]),
+    ("label.match", ["Match", "一致", "匹配", "匹配"]),
+    ("label.source", ["Source", "ソース", "来源", "來源"]),
+    ("label.field", ["Field", "項目", "字段", "欄位"]),
+    ("label.value", ["Value", "内容", "内容", "內容"]),
+    ("label.total", ["Total", "合計", "共", "共"]),
+    ("label.check", ["Check", "項目", "检查项", "檢查項"]),
+    ("label.status", ["Status", "状態", "状态", "狀態"]),
+    ("label.latency", ["Latency", "応答時間", "延迟", "延遲"]),
+    ("label.detail", ["Detail", "詳細", "详情", "詳情"]),
+    ("fmt.minutes", ["{} min", "{} 分", "{} 分钟", "{} 分鐘"]),
+    ("fmt.count", ["{} total", "全 {} 件", "共{}条", "共{}條"]),
+    ("fmt.page_only", ["(page {})", "（{} ページ）", "（第 {} 页）", "（第 {} 頁）"]),
+    ("fmt.page", ["(page {} / {}):", "（{} / {} ページ）:", "（第 {} / {} 页）:", "（第 {} / {} 頁）:"]),
+    // install / view
+    ("install.header", ["Code: {} - {}", "品番: {} - {}", "番号: {} - {}", "番號: {} - {}"]),
+    ("install.no_magnets", ["No magnet links found", "マグネットリンクが見つかりません", "未找到可用的磁力链接", "未找到可用的磁力連結"]),
+    ("install.available", ["Available magnet links:", "利用可能なマグネットリンク:", "可用磁力链接:", "可用磁力連結:"]),
+    ("install.usage", ["Usage:", "使い方:", "使用方法:", "使用方法:"]),
+    ("install.usage_client", ["- Copy a link into your BitTorrent client", "- リンクを BitTorrent クライアントにコピーしてください", "- 复制链接到您的 BT 客户端", "- 複製連結到您的 BT 用戶端"]),
+    ("install.usage_aria2", ["- Or use the command line: aria2c \"<magnet link>\"", "- またはコマンドライン: aria2c \"<マグネットリンク>\"", "- 或使用命令行工具: aria2c \"<磁力链接>\"", "- 或使用命令列工具: aria2c \"<磁力連結>\""]),
+    ("view.opening", ["Opening browser to watch: {}", "ブラウザで開きます: {}", "正在打开浏览器观看: {}", "正在開啟瀏覽器觀看: {}"]),
+    // Candidate picking
+    ("pick.header", ["Multiple candidates found:", "複数の候補が見つかりました:", "找到多个候选结果：", "找到多個候選結果："]),
+    ("pick.prompt", ["Choose [1-{}], Enter to cancel: ", "選択してください [1-{}]（Enter で取消）: ", "请选择 [1-{}]，回车取消: ", "請選擇 [1-{}]，Enter 取消: "]),
+    // Errors
+    ("err.ambiguous", ["{} matched {} candidates; choose one with --pick N", "{} に {} 件の候補があります。--pick N で選択してください", "{} 匹配到 {} 个候选结果，请使用 --pick N 指定", "{} 匹配到 {} 個候選結果，請使用 --pick N 指定"]),
+    ("err.pick_range", ["--pick {} is out of range (1-{})", "--pick {} は範囲外です (1-{})", "--pick {} 超出候选范围 (1-{})", "--pick {} 超出候選範圍 (1-{})"]),
+    ("err.code_mismatch", ["{} returned code {}, which does not match {}", "{} が返した品番 {} は {} と一致しません", "{} 返回的番号 {} 与 {} 不一致", "{} 回傳的番號 {} 與 {} 不一致"]),
+    ("err.not_found", ["{}: code not found", "{}: 品番が見つかりません", "{} 未找到该番号", "{} 未找到該番號"]),
+    ("err.bad_code", ["Unrecognized code", "認識できない品番です", "无法识别的番号", "無法識別的番號"]),
+    ("err.dmm_unconfigured", ["DMM is not configured: set DMM_API_ID and DMM_AFFILIATE_ID", "DMM が未設定です: DMM_API_ID と DMM_AFFILIATE_ID を設定してください", "DMM 未配置：请设置 DMM_API_ID 与 DMM_AFFILIATE_ID", "DMM 未設定：請設定 DMM_API_ID 與 DMM_AFFILIATE_ID"]),
+    ("err.source_disabled", ["Source {} is disabled", "ソース {} は無効です", "数据源 {} 未启用", "資料來源 {} 未啟用"]),
+    ("err.circuit_open", ["{} skipped: too many recent failures, retrying in {} min", "{} をスキップ: 直近の失敗が多いため {} 分後に再試行します", "已跳过 {}：近期失败过多，{} 分钟后重试", "已略過 {}：近期失敗過多，{} 分鐘後重試"]),
+    ("err.invalid_proxy", ["Invalid proxy: {}", "プロキシが不正です: {}", "代理地址无效: {}", "代理位址無效: {}"]),
+    ("err.no_cover", ["{} has no cover", "{} にはジャケットがありません", "{} 没有封面", "{} 沒有封面"]),
+    ("err.no_trailer", ["{} has no official trailer", "{} には公式サンプル動画がありません", "{} 没有官方预告片", "{} 沒有官方預告片"]),
+    ("err.trailer_unresolved", ["No video file found on the trailer player page", "サンプル動画のプレイヤーページに動画ファイルが見つかりません", "预告片播放页中未找到视频文件", "預告片播放頁中未找到影片檔案"]),
+    ("err.write_file", ["Failed to write {}", "{} の書き込みに失敗しました", "写入 {} 失败", "寫入 {} 失敗"]),
+    ("trailer.saved", ["Saved trailer to {}", "サンプル動画を {} に保存しました", "预告片已保存到 {}", "預告片已儲存到 {}"]),
+    ("err.translate_start", ["Cannot run translator `{}`", "翻訳コマンド `{}` を実行できません", "无法运行翻译命令 `{}`", "無法執行翻譯命令 `{}`"]),
+    ("err.translate_failed", ["Translator `{}` exited with {}", "翻訳コマンド `{}` が {} で終了しました", "翻译命令 `{}` 退出，状态 {}", "翻譯命令 `{}` 結束，狀態 {}"]),
+    ("err.translate_timeout", ["Translator `{}` did not finish within {}s", "翻訳コマンド `{}` が {} 秒以内に終わりませんでした", "翻译命令 `{}` 未在 {} 秒内完成", "翻譯命令 `{}` 未在 {} 秒內完成"]),
+    ("err.translate_empty", ["The translator returned nothing", "翻訳結果が空です", "翻译结果为空", "翻譯結果為空"]),
+    ("err.url_unsupported", ["No code found in URL: {}", "URL から品番を特定できません: {}", "无法从 URL 识别番号: {}", "無法從 URL 識別番號: {}"]),
+    ("err.config_dir", ["Cannot determine the config directory", "設定ディレクトリを特定できません", "无法确定配置目录", "無法確定設定目錄"]),
+    ("err.create_config_dir", ["Failed to create the config directory", "設定ディレクトリの作成に失敗しました", "创建配置目录失败", "建立設定目錄失敗"]),
+    ("err.write_history", ["Failed to write history", "履歴の書き込みに失敗しました", "写入历史记录失败", "寫入歷史記錄失敗"]),
+    ("err.template_missing", ["--format template needs --template", "--format template には --template が必要です", "--format template 需要配合 --template 使用", "--format template 需要搭配 --template 使用"]),
+    ("err.template", ["Invalid output template", "出力テンプレートが不正です", "输出模板无效", "輸出範本無效"]),
+    ("err.read_template", ["Failed to read template file {}", "テンプレートファイル {} を読み込めません", "读取模板文件 {} 失败", "讀取範本檔案 {} 失敗"]),
+    ("serve.listening", ["Listening on http://{}", "http://{} で待ち受け中", "正在监听 http://{}", "正在監聽 http://{}"]),
+    ("serve.bind_failed", ["Cannot listen on {}", "{} で待ち受けできません", "无法监听 {}", "無法監聽 {}"]),
+    ("stash.read_failed", ["Failed to read the Stash request from stdin", "stdin から Stash のリクエストを読み込めません", "从 stdin 读取 Stash 请求失败", "從 stdin 讀取 Stash 請求失敗"]),
+    ("stash.no_code", ["No code, title or URL to look up", "検索に使える品番・タイトル・URL がありません", "没有可用于查询的番号、标题或 URL", "沒有可用於查詢的番號、標題或 URL"]),
+    ("stash.no_name", ["Performer name is missing", "出演者名がありません", "缺少演员名", "缺少演員名"]),
+    // Source definitions
+    ("defs.bad_version", ["{}: schema version {} is not supported (expected {}), using the bundled definition", "{}: スキーマバージョン {} には対応していません（{} が必要）。同梱の定義を使います", "{}: 不支持的定义版本 {}（需要 {}），改用内置定义", "{}: 不支援的定義版本 {}（需要 {}），改用內建定義"]),
+    ("defs.bad_selector", ["{}: selector `{}` is invalid, keeping the bundled one: {}", "{}: セレクタ `{}` が不正です。同梱のものを使います: {}", "{}: 选择器 `{}` 无效，沿用内置值: {}", "{}: 選擇器 `{}` 無效，沿用內建值: {}"]),
+    ("defs.bad_pattern", ["{}: pattern `{}` is invalid, keeping the bundled one: {}", "{}: パターン `{}` が不正です。同梱のものを使います: {}", "{}: 正则 `{}` 无效，沿用内置值: {}", "{}: 正規表示式 `{}` 無效，沿用內建值: {}"]),
+    // Doctor
+    ("doctor.ok", ["ok", "正常", "正常", "正常"]),
+    ("doctor.fail", ["FAIL", "異常", "异常", "異常"]),
+    ("doctor.skip", ["skipped", "スキップ", "跳过", "略過"]),
+    ("doctor.config_missing", ["{} not found, using defaults", "{} がありません。既定値を使います", "未找到 {}，使用默认值", "找不到 {}，使用預設值"]),
+    ("doctor.bundled", ["bundled definition", "同梱の定義", "内置定义", "內建定義"]),
+    ("doctor.override", ["overridden by {}", "{} で上書き", "已由 {} 覆盖", "已由 {} 覆寫"]),
+    ("doctor.proxy_none", ["not set (AV_HTTP_PROXY)", "未設定（AV_HTTP_PROXY）", "未设置（AV_HTTP_PROXY）", "未設定（AV_HTTP_PROXY）"]),
+    ("doctor.dmm_off", ["disabled (set AV_USE_DMM=1)", "無効（AV_USE_DMM=1 で有効化）", "未启用（设置 AV_USE_DMM=1）", "未啟用（設定 AV_USE_DMM=1）"]),
+    ("doctor.dmm_ok", ["credentials accepted", "認証情報は有効です", "凭据有效", "憑證有效"]),
+    ("doctor.disabled", ["source disabled", "ソースは無効です", "数据源未启用", "資料來源未啟用"]),
+    ("doctor.canary_ok", ["{}: all expected fields present", "{}: 期待する項目はすべて取得", "{}: 预期字段齐全", "{}: 預期欄位齊全"]),
+    ("doctor.missing", ["{}: missing {}", "{}: 取得できない項目 {}", "{}: 缺少 {}", "{}: 缺少 {}"]),
+    ("doctor.not_found", ["{}: not found", "{}: 見つかりません", "{}: 未找到", "{}: 找不到"]),
+    ("doctor.actor_ok", ["{}: {} titles", "{}: {} 件", "{}: {} 部作品", "{}: {} 部作品"]),
+    ("doctor.actor_empty", ["{}: no titles", "{}: 作品がありません", "{}: 没有作品", "{}: 沒有作品"]),
+    ("doctor.circuit_open", ["circuit was open (retry in {} min)", "サーキットが開いていました（{} 分後に再試行）", "熔断已打开（{} 分钟后重试）", "熔斷已開啟（{} 分鐘後重試）"]),
+    ("doctor.failed", ["{} check(s) failed", "{} 件のチェックが失敗しました", "{} 项检查失败", "{} 項檢查失敗"]),
+    // System helpers
+    ("aria2.missing", ["aria2c not found, please install it first: brew install aria2", "aria2c が見つかりません。先にインストールしてください: brew install aria2", "未检测到 aria2c，请先安装: brew install aria2", "未偵測到 aria2c，請先安裝: brew install aria2"]),
+    ("aria2.start_failed", ["Failed to start aria2c", "aria2c の起動に失敗しました", "启动 aria2c 失败", "啟動 aria2c 失敗"]),
+    ("aria2.failed", ["aria2c download failed, exit code: {}", "aria2c のダウンロードに失敗しました。終了コード: {}", "aria2c 下载失败，退出码: {}", "aria2c 下載失敗，結束代碼: {}"]),
+    ("aria2.done", ["Download complete", "ダウンロード完了", "下载完成", "下載完成"]),
+    ("open.failed", ["Failed to ask the system to open the URI", "システムで URI を開けませんでした", "调用系统打开 URI 失败", "呼叫系統開啟 URI 失敗"]),
+    ("open.cannot", ["The system cannot open: {}", "システムで開けません: {}", "系统无法打开: {}", "系統無法開啟: {}"]),
+    ("open.handed", ["Handed over to the system default handler", "システムの既定のアプリに渡しました", "已交给系统默认的 BT 客户端处理", "已交給系統預設的 BT 用戶端處理"]),
+    // Self update
+    ("update.checking", ["Checking for updates...", "更新を確認しています...", "正在检查更新...", "正在檢查更新..."]),
+    ("update.tmpdir", ["Cannot create a temporary directory", "一時ディレクトリを作成できません", "无法创建临时目录", "無法建立暫存目錄"]),
+    ("update.downloading", ["Downloading installer: {}", "インストーラーをダウンロード中: {}", "下载安装脚本: {}", "下載安裝腳本: {}"]),
+    ("update.download_failed", ["Failed to download the installer", "インストーラーのダウンロードに失敗しました", "下载安装脚本失败", "下載安裝腳本失敗"]),
+    ("update.read_failed", ["Failed to read the installer", "インストーラーの読み込みに失敗しました", "读取安装脚本内容失败", "讀取安裝腳本內容失敗"]),
+    ("update.create_failed", ["Failed to create the temporary installer file", "一時インストーラーファイルの作成に失敗しました", "创建临时安装脚本文件失败", "建立暫存安裝腳本檔案失敗"]),
+    ("update.write_failed", ["Failed to write the installer", "インストーラーの書き込みに失敗しました", "写入安装脚本内容失败", "寫入安裝腳本內容失敗"]),
+    ("update.perm_read_failed", ["Failed to read file permissions", "ファイル権限の取得に失敗しました", "获取文件权限失败", "取得檔案權限失敗"]),
+    ("update.perm_set_failed", ["Failed to set execute permission", "実行権限の設定に失敗しました", "设置执行权限失败", "設定執行權限失敗"]),
+    ("update.exe_path", ["Cannot determine the current executable path", "実行ファイルのパスを特定できません", "无法确定当前可执行文件路径", "無法確定目前執行檔路徑"]),
+    ("update.running", ["Running installer...", "インストーラーを実行しています...", "执行安装脚本...", "執行安裝腳本..."]),
+    ("update.run_failed", ["Failed to run the installer", "インストーラーの実行に失敗しました", "执行安装脚本失败", "執行安裝腳本失敗"]),
+    ("update.ps_create_failed", ["Failed to create the PowerShell script", "PowerShell スクリプトの作成に失敗しました", "创建 PowerShell 脚本文件失败", "建立 PowerShell 腳本檔案失敗"]),
+    ("update.ps_write_failed", ["Failed to write the PowerShell script", "PowerShell スクリプトの書き込みに失敗しました", "写入 PowerShell 脚本内容失败", "寫入 PowerShell 腳本內容失敗"]),
+    ("update.success", ["Updated successfully!", "更新しました！", "更新成功！", "更新成功！"]),
+    ("update.exit_failed", ["Installer failed, exit code: {}", "インストーラーが失敗しました。終了コード: {}", "安装脚本执行失败，退出码: {}", "安裝腳本執行失敗，結束代碼: {}"]),
+    // TUI
+    ("tui.loading_latest", ["Loading latest titles…", "最新作品を読み込み中…", "加载最新番号…", "載入最新番號…"]),
+    ("tui.latest", ["Latest", "最新", "最新", "最新"]),
+    ("tui.count", ["{}: {} items", "{}：{} 件", "{}：共 {} 条", "{}：共 {} 條"]),
+    ("tui.searching", ["Searching {}…", "{} を検索中…", "搜索 {}…", "搜尋 {}…"]),
+    ("tui.search_title", ["Search: {}", "検索：{}", "搜索：{}", "搜尋：{}"]),
+    ("tui.search_failed", ["Search failed: {}", "検索に失敗しました：{}", "搜索失败：{}", "搜尋失敗：{}"]),
+    ("tui.loading", ["Loading {}…", "{} を読み込み中…", "加载 {}…", "載入 {}…"]),
+    ("tui.load_failed", ["Failed to load: {}", "読み込みに失敗しました：{}", "加载失败：{}", "載入失敗：{}"]),
+    ("tui.fetching", ["Fetching {} details…", "{} の詳細を取得中…", "获取 {} 详情…", "取得 {} 詳情…"]),
+    ("tui.loaded", ["{} loaded", "{} を読み込みました", "{} 已加载", "{} 已載入"]),
+    ("tui.no_targets", ["No actors/studio/series to jump to", "移動できる出演者・メーカー・シリーズがありません", "没有可跳转的演员/片商/系列", "沒有可跳轉的演員/片商/系列"]),
+    ("tui.copied", ["Copied {}", "{} をコピーしました", "已复制 {}", "已複製 {}"]),
+    ("tui.cover_opened", ["Opened cover {}", "ジャケットを開きました {}", "已打开封面 {}", "已開啟封面 {}"]),
+    ("tui.cover_failed", ["Failed to open cover: {}", "ジャケットを開けませんでした：{}", "打开封面失败：{}", "開啟封面失敗：{}"]),
+    ("tui.no_cover", ["This title has no cover", "この作品にはジャケットがありません", "该番号没有封面", "該番號沒有封面"]),
+    ("tui.press_enter", ["Press Enter to load details", "Enter で詳細を読み込み", "按 Enter 加载详情", "按 Enter 載入詳情"]),
+    ("tui.detail", ["Details", "詳細", "详情", "詳情"]),
+    ("tui.search_prompt", ["Search: ", "検索： ", "搜索： ", "搜尋： "]),
+    ("tui.keyword_search", [" (keyword search)", "（キーワード検索）", "（关键词搜索）", "（關鍵字搜尋）"]),
+    ("tui.goto", ["Jump to", "移動先", "跳转到", "跳轉到"]),
+    ("tui.help", [
+        "↑↓/jk move  Enter details  g jump  a actor  / search  y copy code  o open cover  J/K scroll  Esc back  q quit",
+        "↑↓/jk 移動  Enter 詳細  g ジャンプ  a 出演者  / 検索  y 品番コピー  o ジャケット  J/K スクロール  Esc 戻る  q 終了",
+        "↑↓/jk 移动  Enter 详情  g 跳转  a 演员  / 搜索  y 复制番号  o 打开封面  J/K 滚动详情  Esc 返回  q 退出",
+        "↑↓/jk 移動  Enter 詳情  g 跳轉  a 演員  / 搜尋  y 複製番號  o 開啟封面  J/K 捲動詳情  Esc 返回  q 離開",
+    ]),
+    // clap help text
+    ("help.about", ["AV CLI: search, inspect and download titles and actor filmographies", "AV CLI: 品番や出演者の作品を検索・表示・ダウンロード", "AV CLI: 搜索、查看与下载番号和演员作品", "AV CLI: 搜尋、查看與下載番號和演員作品"]),
+    ("help.arg.json", ["Output JSON (same as --format json)", "JSON で出力（--format json と同じ）", "统一输出为 JSON（等同于 --format json）", "統一輸出為 JSON（等同於 --format json）"]),
+    ("help.arg.format", ["Output format", "出力形式", "输出格式", "輸出格式"]),
+    ("help.arg.fields", ["Only output these fields, comma separated (e.g. code,title)", "出力する項目、カンマ区切り（例: code,title）", "只输出指定字段，逗号分隔（如 code,title）", "只輸出指定欄位，逗號分隔（如 code,title）"]),
+    ("help.arg.template", ["Template string or file for --format template (e.g. '{{ code }} {{ title }}')", "--format template で使うテンプレート文字列またはファイル（例: '{{ code }} {{ title }}'）", "--format template 使用的模板字符串或模板文件（如 '{{ code }} {{ title }}'）", "--format template 使用的範本字串或範本檔案（如 '{{ code }} {{ title }}'）"]),
+    ("help.arg.debug", ["Print debug logs", "デバッグログを出力", "输出调试日志", "輸出除錯日誌"]),
+    ("help.arg.wrap", ["Wrap long table cells (truncated to the terminal width by default)", "長いセルを折り返す（既定は端末幅で切り詰め）", "表格列过长时换行显示（默认按终端宽度截断）", "表格欄位過長時換行顯示（預設依終端寬度截斷）"]),
+    ("help.arg.columns", ["Table columns and their order, comma separated (e.g. code,title)", "表示する列と順序、カンマ区切り（例: code,title）", "表格显示的列及顺序，逗号分隔（如 code,title）", "表格顯示的欄位及順序，逗號分隔（如 code,title）"]),
+    ("help.arg.lang", ["Output language: en, ja, zh-hans, zh-hant", "表示言語: en, ja, zh-hans, zh-hant", "输出语言：en、ja、zh-hans、zh-hant", "輸出語言：en、ja、zh-hans、zh-hant"]),
+    ("help.arg.uncen", ["Only show uncensored titles (title/tag heuristics)", "無修正作品のみ表示（タイトル・タグによる推定）", "只显示无马赛克（基于标题/标签的启发式判断）", "只顯示無馬賽克（基於標題/標籤的啟發式判斷）"]),
+    ("help.arg.genre", ["Only show titles of this genre (canonical ID, name in any language, or a source's tag; fetches each title's details)", "このジャンルの作品のみ表示（ジャンル ID・各言語の名前・ソースのタグ。作品ごとに詳細を取得）", "只显示该类别的作品（类别 ID、任意语言的名称或数据源标签；需逐条获取详情）", "只顯示該類別的作品（類別 ID、任意語言的名稱或資料來源標籤；需逐筆取得詳情）"]),
+    ("help.arg.related", ["Also list related titles (similar, also viewed, same series)", "関連作品（おすすめ・閲覧履歴・同シリーズ）も表示", "同时列出相关作品（相似、看过的人也看过、同系列）", "同時列出相關作品（相似、看過的人也看過、同系列）"]),
+    ("help.arg.pick", ["Pick the Nth candidate when several titles match", "候補が複数ある場合に N 番目を選択", "多个候选结果时选择第 N 个", "多個候選結果時選擇第 N 個"]),
+    ("help.arg.initial", ["Filter by the initial of the reading (e.g. あ)", "読みの頭文字で絞り込み（例: あ）", "按读音首字母过滤（如 あ）", "依讀音首字母過濾（如 あ）"]),
+    ("help.cmd.install", ["Get magnet links for a code", "品番のマグネットリンクを取得", "获取该番号对应的磁力链接", "取得該番號對應的磁力連結"]),
+    ("help.cmd.detail", ["Show details of a code", "品番の詳細を表示", "展示该番号的详细信息", "顯示該番號的詳細資訊"]),
+    ("help.cmd.list", ["List all titles of an actor", "出演者の全作品を一覧表示", "列出该演员的所有番号", "列出該演員的所有番號"]),
+    ("help.cmd.search", ["Search actors or codes", "出演者・品番を検索", "搜索演员或番号", "搜尋演員或番號"]),
+    ("help.cmd.top", ["Show the latest titles (20 by default)", "最新作品を表示（既定 20 件）", "查看最新的番（默认 20 条）", "查看最新的番（預設 20 條）"]),
+    ("help.cmd.actors", ["Actor popularity ranking (paged)", "出演者人気ランキング（ページ単位）", "演员热度排行榜（分页）", "演員熱度排行榜（分頁）"]),
+    ("help.cmd.browse", ["Browse DMM makers/series/genres/floors (needs DMM_API_ID and DMM_AFFILIATE_ID)", "DMM のメーカー・シリーズ・ジャンル・フロアを閲覧（DMM_API_ID と DMM_AFFILIATE_ID が必要）", "浏览 DMM 的片商/系列/类别/楼层（需要 DMM_API_ID 与 DMM_AFFILIATE_ID）", "瀏覽 DMM 的片商/系列/類別/樓層（需要 DMM_API_ID 與 DMM_AFFILIATE_ID）"]),
+    ("help.cmd.reviews", ["Show user ratings and reviews (JavDB, JavLibrary; paged)", "ユーザー評価とレビューを表示（JavDB・JavLibrary、ページ単位）", "查看用户评分与评论（JavDB、JavLibrary，分页）", "查看用戶評分與評論（JavDB、JavLibrary，分頁）"]),
+    ("help.cmd.trailer", ["Download the official DMM trailer (<CODE>-trailer.mp4)", "DMM 公式サンプル動画をダウンロード（<品番>-trailer.mp4）", "下载 DMM 官方预告片（<番号>-trailer.mp4）", "下載 DMM 官方預告片（<番號>-trailer.mp4）"]),
+    ("help.arg.trailer.out", ["Directory to save into", "保存先ディレクトリ", "保存目录", "儲存目錄"]),
+    ("help.cmd.view", ["Open the video in a browser", "ブラウザで動画を開く", "在浏览器中打开观看视频", "在瀏覽器中開啟觀看影片"]),
+    ("help.cmd.tui", ["Interactively browse search results and details", "検索結果と詳細をインタラクティブに閲覧", "交互式浏览搜索结果与详情", "互動式瀏覽搜尋結果與詳情"]),
+    ("help.cmd.serve", ["Run a local HTTP API (/detail, /search, /actors, /list, /nfo, /cover)", "ローカル HTTP API を起動（/detail、/search、/actors、/list、/nfo、/cover）", "启动本地 HTTP API（/detail、/search、/actors、/list、/nfo、/cover）", "啟動本機 HTTP API（/detail、/search、/actors、/list、/nfo、/cover）"]),
+    ("help.arg.serve.bind", ["Address to listen on", "待ち受けアドレス", "监听地址", "監聽位址"]),
+    ("help.cmd.stash", ["Stash script scraper entry point (JSON on stdin); `av stash yaml` prints the scraper config", "Stash スクリプトスクレイパーの入口（stdin で JSON を受け取る）。`av stash yaml` で設定を出力", "Stash 脚本刮削器入口（从 stdin 读取 JSON）；`av stash yaml` 输出刮削器配置", "Stash 腳本刮削器入口（從 stdin 讀取 JSON）；`av stash yaml` 輸出刮削器設定"]),
+    ("help.cmd.doctor", ["Check the configuration and test every source against known titles; exits non-zero on failures (cron friendly)", "設定を確認し、既知の品番で各ソースをテスト。異常があれば非ゼロで終了（cron 向け）", "检查配置并用已知番号测试各数据源，有故障时以非零状态退出（适合 cron）", "檢查設定並用已知番號測試各資料來源，有故障時以非零狀態結束（適合 cron）"]),
+    ("help.cmd.schema", ["Print the JSON Schema of --format json output of a kind", "--format json の出力（種類別）の JSON Schema を出力", "输出 --format json 各类结果的 JSON Schema", "輸出 --format json 各類結果的 JSON Schema"]),
+    ("help.cmd.completions", ["Generate shell completion scripts (bash/zsh/fish/powershell)", "シェル補完スクリプトを生成（bash/zsh/fish/powershell）", "生成 shell 补全脚本（bash/zsh/fish/powershell）", "產生 shell 補全腳本（bash/zsh/fish/powershell）"]),
+    ("help.cmd.update", ["Update to the latest version", "最新バージョンに更新", "自动更新到最新版本", "自動更新到最新版本"]),
+];
@@ -0,0 +1,43 @@
+//! Lookup of AV titles, actors and magnet links across JavDB, JavLibrary,
+//! Sukebei and the DMM Web Service.
+//!
+//! ```no_run
+//! # async fn demo() -> anyhow::Result<()> {
+//! let client = av::Client::builder().timeout(std::time::Duration::from_secs(20)).build()?;
+//! let detail = client.detail("SSIS-001").await?;
+//! println!("{} {}", detail.code, detail.title);
+//! # Ok(())
+//! # }
+//! ```
+
+pub mod classify;
+pub mod client;
+pub mod code;
+pub mod nfo;
+mod mirrors;
+mod scraper;
+pub mod sources;
+pub mod title;
+pub mod translate;
+pub mod types;
+
+// Support for the `av` binary (message catalogue, config file, persisted state);
+// public only so the binary can reach it, and not covered by semver.
+#[doc(hidden)]
+pub mod breaker;
+#[doc(hidden)]
+pub mod config;
+#[doc(hidden)]
+pub mod genres;
+#[doc(hidden)]
+pub mod i18n;
+#[doc(hidden)]
+pub mod util;
+
+pub use classify::{Censorship, Classification};
+pub use client::{Client, ClientBuilder, Source};
+pub use code::CodeKey;
+pub use i18n::Lang;
+pub use scraper::{AmbiguousMatch, BadRequest, NotFound};
+pub use translate::Translator;
+pub use types::{ActorItem, ActorPage, AvDetail, AvItem, Candidate, CastMember, CastRole, Date, Image, Localized, MagnetInfo, Rating, RelatedItem, Relation, Review, ReviewPage, Runtime, Trailer, Translation};
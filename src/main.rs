@@ -1,27 +1,63 @@
 use anyhow::{Result};
 use colored::Colorize;
-use clap::{Parser, Subcommand};
+use clap::{Command, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
 
-mod scraper;
-mod types;
-mod util;
-mod sources;
+use av::{i18n, sources, types, util, AmbiguousMatch, Client, ClientBuilder};
+
+mod completions;
+mod display;
+mod doctor;
+mod history;
+mod output;
+mod serve;
+mod stash;
+mod system;
+mod table;
+mod tui;
 
 #[derive(Parser, Debug)]
 #[command(name = "av", version, about = "AV CLI: 搜索、查看与下载番号和演员作品", long_about = None)]
 struct Cli {
-    /// 统一输出为 JSON
+    /// 统一输出为 JSON（等同于 --format json）
     #[arg(long, global = true)]
     json: bool,
 
+    /// 输出格式
+    #[arg(long, global = true, value_enum, value_name = "FORMAT")]
+    format: Option<output::Format>,
+
+    /// 只输出指定字段，逗号分隔（如 code,title）
+    #[arg(long, global = true, value_name = "FIELDS")]
+    fields: Option<String>,
+
+    /// --format template 使用的模板字符串或模板文件（如 '{{ code }} {{ title }}'）
+    #[arg(long, global = true, value_name = "TEMPLATE")]
+    template: Option<String>,
+
     /// 输出调试日志
     #[arg(long, global = true)]
     debug: bool,
 
+    /// 表格列过长时换行显示（默认按终端宽度截断）
+    #[arg(long, global = true)]
+    wrap: bool,
+
+    /// 表格显示的列及顺序，逗号分隔（如 code,title）
+    #[arg(long, global = true, value_name = "COLS")]
+    columns: Option<String>,
+
+    /// 输出语言：en、ja、zh-hans、zh-hant
+    #[arg(long, global = true, value_name = "LANG")]
+    lang: Option<String>,
+
     /// 只显示无马赛克（基于标题/标签的启发式判断）
     #[arg(long = "uncen", short = 'u', alias = "nomo", global = true)]
     uncen: bool,
 
+    /// 只显示该类别的作品（类别 ID、任意语言的名称或数据源标签；需逐条获取详情）
+    #[arg(long, global = true, value_name = "GENRE")]
+    genre: Option<String>,
+
     #[command(subcommand)]
     command: Commands,
 }
@@ -30,10 +66,23 @@ struct Cli {
 enum Commands {
     /// 获取该番号对应的磁力链接
     #[command(visible_alias = "get")]
-    Install { code: String },
+    Install {
+        code: String,
+        /// 多个候选结果时选择第 N 个
+        #[arg(long)]
+        pick: Option<usize>,
+    },
 
     /// 展示该番号的详细信息
-    Detail { code: String },
+    Detail {
+        code: String,
+        /// 多个候选结果时选择第 N 个
+        #[arg(long)]
+        pick: Option<usize>,
+        /// 同时列出相关作品
+        #[arg(long)]
+        related: bool,
+    },
 
     /// 列出该演员的所有番号
     #[command(visible_alias = "ls")]
@@ -48,37 +97,175 @@ enum Commands {
     /// 演员热度排行榜（分页）
     Actors { #[arg(short, long, default_value_t = 1)] page: usize, #[arg(short='n', long, default_value_t = 50)] per_page: usize },
 
+    /// 浏览 DMM 的片商/系列/类别/楼层（需要 DMM_API_ID 与 DMM_AFFILIATE_ID）
+    Browse {
+        kind: BrowseKind,
+        /// 按读音首字母过滤（如 あ）
+        #[arg(long)]
+        initial: Option<String>,
+        #[arg(short, long, default_value_t = 1)]
+        page: usize,
+        #[arg(short = 'n', long, default_value_t = 50)]
+        per_page: usize,
+    },
+
+    /// 查看用户评分与评论（JavDB、JavLibrary，分页）
+    Reviews {
+        code: String,
+        #[arg(short, long, default_value_t = 1)]
+        page: usize,
+        /// 多个候选结果时选择第 N 个
+        #[arg(long)]
+        pick: Option<usize>,
+    },
+
+    /// 下载 DMM 官方预告片（<番号>-trailer.mp4）
+    Trailer {
+        code: String,
+        /// 保存目录
+        #[arg(short, long, default_value = ".")]
+        out: std::path::PathBuf,
+    },
+
     /// 在浏览器中打开观看视频
     #[command(visible_alias = "see")]
     View { code: String },
 
+    /// 交互式浏览搜索结果与详情
+    Tui { query: Option<String> },
+
+    /// 启动本地 HTTP API（/detail、/search、/actors、/list、/nfo、/cover）
+    Serve {
+        /// 监听地址
+        #[arg(long, default_value = "127.0.0.1:8910")]
+        bind: std::net::SocketAddr,
+    },
+
+    /// Stash 脚本刮削器入口（从 stdin 读取 JSON）；`av stash yaml` 输出刮削器配置
+    Stash { mode: stash::Mode },
+
+    /// 检查配置并用已知番号测试各数据源，有故障时以非零状态退出（适合 cron）
+    Doctor,
+
+    /// 输出 --format json 各类结果的 JSON Schema
+    Schema { kind: output::Kind },
+
+    /// 生成 shell 补全脚本（bash/zsh/fish/powershell）
+    Completions { shell: clap_complete::Shell },
+
+    /// 输出历史记录中的番号或演员，供补全脚本调用
+    #[command(name = "__complete", hide = true)]
+    Complete { kind: CompleteKind, prefix: Option<String> },
+
     /// 自动更新到最新版本
     #[command(name = "update", visible_alias = "self-update")]
     SelfUpdate,
 }
 
+impl Commands {
+    /// Kind of the structured output, for commands that print one.
+    fn output_kind(&self) -> Option<output::Kind> {
+        match self {
+            Commands::Detail { .. } => Some(output::Kind::Detail),
+            Commands::List { .. } | Commands::Search { .. } | Commands::Top { .. } => Some(output::Kind::Items),
+            Commands::Actors { .. } => Some(output::Kind::Actors),
+            Commands::Browse { .. } => Some(output::Kind::Entries),
+            Commands::Reviews { .. } => Some(output::Kind::Reviews),
+            Commands::Install { .. } => Some(output::Kind::Magnets),
+            Commands::Trailer { .. } => Some(output::Kind::File),
+            Commands::View { .. } => Some(output::Kind::Url),
+            _ => None,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum BrowseKind {
+    Makers,
+    Series,
+    Genres,
+    Floors,
+}
+
+impl From<BrowseKind> for sources::dmm::Facet {
+    fn from(k: BrowseKind) -> Self {
+        match k {
+            BrowseKind::Makers => Self::Makers,
+            BrowseKind::Series => Self::Series,
+            BrowseKind::Genres => Self::Genres,
+            BrowseKind::Floors => Self::Floors,
+        }
+    }
+}
+
+#[derive(ValueEnum, Clone, Copy, Debug)]
+enum CompleteKind {
+    Codes,
+    Actors,
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
-    let cli = Cli::parse();
+    i18n::init(i18n::lang_arg_from_env().as_deref());
+    let cli = match Cli::from_arg_matches(&localize_command(Cli::command()).get_matches()) {
+        Ok(cli) => cli,
+        Err(e) => e.exit(),
+    };
     util::set_debug(cli.debug);
+    table::set_options(cli.wrap, cli.columns.as_deref());
+    let format = match (cli.format, cli.json, &cli.template) {
+        (Some(f), _, _) => f,
+        (None, true, _) => output::Format::Json,
+        (None, false, Some(_)) => output::Format::Template,
+        (None, false, None) => output::Format::Table,
+    };
+    output::set_options(format, cli.fields.as_deref(), cli.template.as_deref())?;
+    // Doctor reports a bad proxy instead of failing on it like every other command; schema needs no client
+    match cli.command {
+        Commands::Doctor => return doctor::run().await,
+        Commands::Schema { kind } => return output::print_schema(kind),
+        _ => {}
+    }
+    let kind = cli.command.output_kind();
+    let result = run(cli).await;
+    if let (Err(err), Some(kind)) = (&result, kind) {
+        output::print_failure(kind, err)?;
+    }
+    result
+}
+
+async fn run(cli: Cli) -> Result<()> {
+    let builder = ClientBuilder::from_env().language(i18n::lang());
+    let client = match cli.command {
+        Commands::Serve { .. } => builder.cache_ttl(serve::CACHE_TTL).build()?,
+        Commands::Stash { .. } => builder.cache_ttl(stash::CACHE_TTL).build()?,
+        _ => builder.build()?,
+    };
 
     match cli.command {
-        Commands::Install { code } => {
-            let detail = scraper::fetch_detail(&code).await?;
+        Commands::Install { code, pick } => {
+            let detail = fetch_detail_resolving(&client, &code, pick).await?;
+            history::record(&[&detail.code], &detail.actor_names);
+            if output::is_structured() {
+                let mut magnets = detail.magnet_infos.clone();
+                magnets.sort_by_key(|m| std::cmp::Reverse(m.seeders.unwrap_or(0)));
+                let links = if magnets.is_empty() { detail.magnets.clone() } else { Vec::new() };
+                return output::print(output::Kind::Magnets, &output::Magnets { code: detail.code, title: detail.title, magnets, links });
+            }
             
             // 显示所有可用的磁力链接，按种子数排序
-            println!("番号: {} - {}", code.bold(), detail.title);
+            println!("{}", i18n::tf("install.header", &[&code.bold(), &detail.title]));
             
             if detail.magnet_infos.is_empty() && detail.magnets.is_empty() {
-                println!("{}", "未找到可用的磁力链接".red().bold());
+                println!("{}", i18n::t("install.no_magnets").red().bold());
             } else {
-                println!("\n{}", "可用磁力链接:".green().bold());
+                println!("\n{}", i18n::t("install.available").green().bold());
                 
                 // 先显示有详细信息的磁力链接
                 if !detail.magnet_infos.is_empty() {
                     // 按种子数排序
                     let mut sorted_magnets = detail.magnet_infos.clone();
-                    sorted_magnets.sort_by(|a, b| b.seeders.unwrap_or(0).cmp(&a.seeders.unwrap_or(0)));
+                    sorted_magnets.sort_by_key(|m| std::cmp::Reverse(m.seeders.unwrap_or(0)));
                     
                     for (i, m) in sorted_magnets.iter().enumerate() {
                         let mut info = String::new();
@@ -99,78 +286,213 @@ async fn main() -> Result<()> {
                     }
                 }
                 
-                println!("\n{}", "使用方法:".yellow().bold());
-                println!("- 复制链接到您的 BT 客户端");
-                println!("- 或使用命令行工具: aria2c \"<磁力链接>\"");
+                println!("\n{}", i18n::t("install.usage").yellow().bold());
+                println!("{}", i18n::t("install.usage_client"));
+                println!("{}", i18n::t("install.usage_aria2"));
             }
             
             Ok(())
         }
-        Commands::Detail { code } => {
+        Commands::Detail { code, pick, related } => {
             util::debug(format!("detail: fetching {}", code));
-            let detail = scraper::fetch_detail(&code).await?;
-            if cli.json {
-                util::print_output(&detail, true);
+            let detail = fetch_detail_resolving(&client, &code, pick).await?;
+            history::record(&[&detail.code], &detail.actor_names);
+            if output::is_structured() {
+                output::print(output::Kind::Detail, &detail)?;
             } else {
-                util::print_detail_human(&detail);
+                display::print_detail_human(&detail);
+                if related { display::print_related_table(&detail.related); }
             }
             Ok(())
         }
         Commands::List { actor } => {
-            let mut items = scraper::list_actor_titles(&actor).await?;
+            let mut items = client.actor_titles(&actor).await?;
+            if !items.is_empty() {
+                history::record(&[], std::slice::from_ref(&actor));
+            }
             if cli.uncen {
-                items.retain(|i| util::looks_uncensored(&i.title));
+                items.retain(|i| i.censorship.is_uncensored());
+            }
+            if let Some(genre) = &cli.genre {
+                items = retain_genre(&client, items, genre).await;
             }
-            if cli.json {
-                util::print_output(&items, true);
+            if output::is_structured() {
+                output::print(output::Kind::Items, &items)?;
             } else {
-                util::print_items_table(&items);
+                display::print_items_table(&items);
             }
             Ok(())
         }
         Commands::Search { query } => {
-            let mut items = scraper::search(&query).await?;
+            let mut items = client.search(&query).await?;
             if cli.uncen {
-                items.retain(|i| util::looks_uncensored(&i.title));
+                items.retain(|i| i.censorship.is_uncensored());
             }
-            if cli.json {
-                util::print_output(&items, true);
+            if let Some(genre) = &cli.genre {
+                items = retain_genre(&client, items, genre).await;
+            }
+            if output::is_structured() {
+                output::print(output::Kind::Items, &items)?;
             } else {
-                util::print_items_table(&items);
+                display::print_items_table(&items);
             }
             Ok(())
         }
         Commands::Top { limit } => {
-            let mut items = scraper::top(limit).await?;
+            let mut items = client.latest(limit).await?;
             if cli.uncen {
-                items.retain(|i| util::looks_uncensored(&i.title));
+                items.retain(|i| i.censorship.is_uncensored());
+            }
+            if let Some(genre) = &cli.genre {
+                items = retain_genre(&client, items, genre).await;
             }
-            if cli.json {
-                util::print_output(&items, true);
+            if output::is_structured() {
+                output::print(output::Kind::Items, &items)?;
             } else {
-                util::print_items_table(&items);
+                display::print_items_table(&items);
             }
             Ok(())
         }
         Commands::Actors { page, per_page } => {
-            let (actors, total) = scraper::actors(page, per_page, cli.uncen).await?;
-            if cli.json {
-                util::print_output(&(actors, total), true);
+            let ranking = client.actors(page, per_page, cli.uncen).await?;
+            if output::is_enveloped() {
+                output::print(output::Kind::Actors, &ranking)?;
+            } else if output::is_structured() {
+                // Record formats get one row per actor
+                output::print(output::Kind::Actors, &ranking.actors)?;
+            } else {
+                display::print_actors_table(&ranking.actors, page, per_page, ranking.total);
+            }
+            Ok(())
+        }
+        Commands::Reviews { code, page, pick } => {
+            let reviews = client.reviews(&code, page, pick).await?;
+            if output::is_enveloped() {
+                output::print(output::Kind::Reviews, &reviews)?;
+            } else if output::is_structured() {
+                output::print(output::Kind::Reviews, &reviews.reviews)?;
+            } else {
+                display::print_reviews(&reviews);
+            }
+            Ok(())
+        }
+        Commands::Trailer { code, out } => {
+            let path = client.download_trailer(&code, &out).await?;
+            if output::is_structured() {
+                output::print(output::Kind::File, &output::File { path })?;
             } else {
-                util::print_actors_table(&actors, page, per_page, total);
+                println!("{}", i18n::tf("trailer.saved", &[&path.display()]));
+            }
+            Ok(())
+        }
+        Commands::Browse { kind, initial, page, per_page } => {
+            let entries = client.browse(kind.into(), initial.as_deref(), page, per_page).await?;
+            if output::is_structured() {
+                output::print(output::Kind::Entries, &entries)?;
+            } else {
+                display::print_entries_table(&entries);
             }
             Ok(())
         }
         Commands::View { code } => {
             util::debug(format!("view: finding play URL for {}", code));
-            let play_url = scraper::get_play_url(&code).await?;
-            println!("Opening browser to watch: {}", play_url);
-            util::open_browser_url(&play_url).await?;
+            let play_url = client.play_url(&code).await?;
+            history::record(&[&code], &[]);
+            if output::is_structured() {
+                output::print(output::Kind::Url, &output::Url { url: play_url.clone() })?;
+            } else {
+                println!("{}", i18n::tf("view.opening", &[&play_url]));
+            }
+            system::open_browser_url(&play_url).await?;
+            Ok(())
+        }
+        Commands::Tui { query } => tui::run(client, query, cli.uncen).await,
+        Commands::Serve { bind } => serve::run(client, bind).await,
+        Commands::Stash { mode } => stash::run(client, mode).await,
+        Commands::Doctor | Commands::Schema { .. } => unreachable!("handled before the client is built"),
+        Commands::Completions { shell } => {
+            print!("{}", completions::render(shell, &mut localize_command(Cli::command())));
+            Ok(())
+        }
+        Commands::Complete { kind, prefix } => {
+            let history = history::load();
+            let entries = match kind {
+                CompleteKind::Codes => history.codes,
+                CompleteKind::Actors => history.actors,
+            };
+            let prefix = prefix.unwrap_or_default().to_lowercase();
+            for e in entries.iter().filter(|e| e.to_lowercase().starts_with(&prefix)) {
+                println!("{}", e);
+            }
             Ok(())
         }
         Commands::SelfUpdate => {
-            util::self_update().await?;
+            system::self_update().await?;
             Ok(())
         }
     }
 }
+
+/// Items whose detail has `genre`. Listings carry no genres, so this looks up every item.
+async fn retain_genre(client: &Client, items: Vec<types::AvItem>, genre: &str) -> Vec<types::AvItem> {
+    let mut kept = Vec::new();
+    for item in items {
+        match client.detail(&item.code).await {
+            Ok(detail) if detail.has_genre(genre) => kept.push(item),
+            Ok(_) => {}
+            Err(e) => util::debug(format!("genre filter: {}: {:#}", item.code, e)),
+        }
+    }
+    kept
+}
+
+/// `fetch_detail`, asking the user to choose when several titles share the code.
+/// Non-interactive runs print the candidates (in the `--format` output when structured) and fail.
+async fn fetch_detail_resolving(client: &Client, code: &str, pick: Option<usize>) -> Result<types::AvDetail> {
+    let err = match client.detail_pick(code, pick).await {
+        Ok(detail) => return Ok(detail),
+        Err(err) => err,
+    };
+    let Some(ambiguous) = err.downcast_ref::<AmbiguousMatch>() else { return Err(err) };
+    // The envelope of a failed command carries the candidates
+    if output::is_enveloped() {
+        return Err(err);
+    }
+    if output::is_structured() {
+        output::print(output::Kind::Ambiguous, &ambiguous.candidates)?;
+        return Err(err);
+    }
+    display::print_candidates_table(&ambiguous.candidates);
+    if display::is_interactive() {
+        if let Some(n) = display::prompt_pick(ambiguous.candidates.len()) {
+            return client.detail_pick(code, Some(n)).await;
+        }
+    }
+    Err(err)
+}
+
+/// Replace about/help strings of the clap definition with catalogue entries:
+/// `help.cmd.<path>` for commands and `help.arg.<path>.<id>` or `help.arg.<id>` for arguments.
+pub fn localize_command(cmd: Command) -> Command {
+    localize(cmd, "")
+}
+
+fn localize(mut cmd: Command, path: &str) -> Command {
+    let about_key = if path.is_empty() { "help.about".to_string() } else { format!("help.cmd.{}", path) };
+    if let Some(about) = i18n::lookup(&about_key) {
+        cmd = cmd.about(about);
+    }
+    let ids: Vec<String> = cmd.get_arguments().map(|a| a.get_id().to_string()).collect();
+    for id in ids {
+        let help = i18n::lookup(&format!("help.arg.{}.{}", path, id)).or_else(|| i18n::lookup(&format!("help.arg.{}", id)));
+        if let Some(help) = help {
+            cmd = cmd.mut_arg(id, |a| a.help(help));
+        }
+    }
+    let subs: Vec<String> = cmd.get_subcommands().map(|s| s.get_name().to_string()).collect();
+    for name in subs {
+        let sub_path = if path.is_empty() { name.clone() } else { format!("{}.{}", path, name) };
+        cmd = cmd.mut_subcommand(&name, |sc| localize(sc, &sub_path));
+    }
+    cmd
+}
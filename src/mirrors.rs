@@ -0,0 +1,142 @@
+//! Mirror failover for the HTML sources. Each source has an ordered list of base
+//! URLs; requests go to the healthiest one and move on to the next on connection
+//! errors or error statuses. Health is kept in `mirrors.json` under the cache dir
+//! so a dead mirror is not retried first on every run.
+
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::sync::{Mutex, OnceLock};
+
+use crate::breaker;
+use crate::client::{self, Source};
+use crate::util;
+
+const STATE_FILE: &str = "mirrors.json";
+
+/// A failed mirror is tried after the healthy ones for this long.
+const COOLDOWN_SECS: u64 = 30 * 60;
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+struct Health {
+    /// Consecutive failures
+    failures: u32,
+    /// Unix time of the latest failure
+    last_failure: u64,
+}
+
+type State = BTreeMap<String, BTreeMap<String, Health>>;
+
+static STATE: OnceLock<Mutex<State>> = OnceLock::new();
+
+fn state() -> &'static Mutex<State> {
+    STATE.get_or_init(|| Mutex::new(util::read_state(STATE_FILE)))
+}
+
+fn record(source: Source, base: &str, ok: bool) {
+    let Ok(mut state) = state().lock() else { return };
+    let entry = state.entry(source.name().to_string()).or_default();
+    if ok {
+        // Only a recovery changes anything worth writing
+        if entry.remove(base).is_some() { util::write_state(STATE_FILE, &*state); }
+    } else {
+        let h = entry.entry(base.to_string()).or_default();
+        h.failures += 1;
+        h.last_failure = util::now_secs();
+        util::write_state(STATE_FILE, &*state);
+    }
+}
+
+/// Mirrors of `source` in the order to try them: configured order, except that
+/// mirrors which failed recently go last, fewest failures first.
+pub(crate) fn ordered(source: Source) -> Vec<String> {
+    let mut bases = client::settings().mirrors(source);
+    let Ok(state) = state().lock() else { return bases };
+    let Some(health) = state.get(source.name()) else { return bases };
+    let cutoff = util::now_secs().saturating_sub(COOLDOWN_SECS);
+    let penalty = |base: &String| match health.get(base) {
+        Some(h) if h.last_failure >= cutoff => h.failures,
+        _ => 0,
+    };
+    bases.sort_by_key(penalty);
+    bases
+}
+
+/// A page and the mirror that served it, for resolving relative links.
+pub(crate) struct Page {
+    pub(crate) base: String,
+    pub(crate) status: u16,
+    pub(crate) body: String,
+}
+
+/// GET `url_for(base)` on the mirrors of `source` until one succeeds. 404 and 410
+/// mean the page does not exist, so they are returned without trying other mirrors.
+pub(crate) async fn fetch(c: &reqwest::Client, source: Source, url_for: impl Fn(&str) -> String) -> Result<Page> {
+    breaker::check(source)?;
+    let result = fetch_any(c, source, url_for).await;
+    // A missing page still proves the source is up
+    let alive = match &result {
+        Ok(_) => true,
+        Err(e) => e.downcast_ref::<reqwest::Error>().and_then(|r| r.status()).is_some_and(|s| is_gone(s.as_u16())),
+    };
+    breaker::record(source, alive);
+    result
+}
+
+fn is_gone(status: u16) -> bool {
+    matches!(status, 404 | 410)
+}
+
+async fn fetch_any(c: &reqwest::Client, source: Source, url_for: impl Fn(&str) -> String) -> Result<Page> {
+    let mut last_err: Option<anyhow::Error> = None;
+    for base in ordered(source) {
+        let url = url_for(&base);
+        let resp = match c.get(&url).send().await {
+            Ok(resp) => resp,
+            Err(e) => {
+                util::debug(format!("mirror {} failed: {}", base, e));
+                record(source, &base, false);
+                last_err = Some(e.into());
+                continue;
+            }
+        };
+        let status = resp.status();
+        if status.is_success() {
+            record(source, &base, true);
+            let body = resp.text().await?;
+            return Ok(Page { base, status: status.as_u16(), body });
+        }
+        let err = resp.error_for_status().expect_err("non-success status");
+        if is_gone(status.as_u16()) {
+            return Err(err.into());
+        }
+        util::debug(format!("mirror {} answered {}", base, status));
+        record(source, &base, false);
+        last_err = Some(err.into());
+    }
+    Err(last_err.unwrap_or_else(|| anyhow::anyhow!("no mirror configured for {:?}", source)))
+}
+
+/// Like [`fetch`] for an absolute URL: when it lives on one of `source`'s mirrors,
+/// the same path is tried on the others too.
+pub(crate) async fn fetch_url(c: &reqwest::Client, source: Source, url: &str) -> Result<Page> {
+    let bases = client::settings().mirrors(source);
+    match bases.iter().find(|b| url.starts_with(b.as_str())) {
+        Some(base) => {
+            let path = url[base.len()..].to_string();
+            fetch(c, source, |b| format!("{}{}", b, path)).await
+        }
+        None => {
+            let resp = c.get(url).send().await?;
+            let status = resp.status().as_u16();
+            let body = resp.error_for_status()?.text().await?;
+            Ok(Page { base: String::new(), status, body })
+        }
+    }
+}
+
+/// Whether `url` points at one of `source`'s mirrors.
+pub(crate) fn owns(source: Source, url: &str) -> bool {
+    client::settings().mirrors(source).iter().any(|b| url.starts_with(b.as_str()))
+}
@@ -0,0 +1,67 @@
+use crate::genres;
+use crate::types::AvDetail;
+
+fn escape(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    for ch in s.chars() {
+        match ch {
+            '&' => out.push_str("&amp;"),
+            '<' => out.push_str("&lt;"),
+            '>' => out.push_str("&gt;"),
+            '"' => out.push_str("&quot;"),
+            '\'' => out.push_str("&apos;"),
+            c if (c as u32) < 0x20 && !matches!(c, '\n' | '\t' | '\r') => {}
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+fn tag(out: &mut String, name: &str, value: &str) {
+    if value.is_empty() { return; }
+    out.push_str(&format!("  <{0}>{1}</{0}>\n", name, escape(value)));
+}
+
+/// Kodi/Jellyfin/Emby `movie.nfo` for a title.
+pub fn to_nfo(d: &AvDetail) -> String {
+    let mut out = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n<movie>\n");
+    let title = if d.clean_title.is_empty() { &d.title } else { &d.clean_title };
+    tag(&mut out, "title", format!("{} {}", d.code, title).trim());
+    tag(&mut out, "originaltitle", if d.original_title.is_empty() { &d.title } else { &d.original_title });
+    tag(&mut out, "sorttitle", &d.code);
+    out.push_str(&format!("  <uniqueid type=\"av\" default=\"true\">{}</uniqueid>\n", escape(&d.code)));
+    tag(&mut out, "id", &d.code);
+    if let Some(plot) = &d.plot {
+        tag(&mut out, "plot", plot);
+        tag(&mut out, "outline", plot);
+    }
+    if let Some(date) = d.release_date {
+        tag(&mut out, "premiered", &date.to_string());
+        tag(&mut out, "releasedate", &date.to_string());
+        tag(&mut out, "year", &date.year.to_string());
+    }
+    if let Some(duration) = d.duration { tag(&mut out, "runtime", &duration.minutes().to_string()); }
+    if let Some(rating) = d.rating {
+        let votes = rating.votes.map(|v| format!("\n      <votes>{}</votes>", v)).unwrap_or_default();
+        out.push_str(&format!(
+            "  <ratings>\n    <rating name=\"default\" max=\"{}\" default=\"true\">\n      <value>{}</value>{}\n    </rating>\n  </ratings>\n",
+            rating.scale, rating.value, votes
+        ));
+    }
+    if let Some(v) = &d.director { tag(&mut out, "director", v); }
+    if let Some(v) = &d.studio { tag(&mut out, "studio", v); }
+    if let Some(v) = &d.label { tag(&mut out, "label", v); }
+    if let Some(v) = &d.series {
+        out.push_str(&format!("  <set>\n    <name>{}</name>\n  </set>\n", escape(v)));
+    }
+    for g in genres::labels(&d.genres) { tag(&mut out, "genre", &g); }
+    for name in &d.actor_names {
+        out.push_str(&format!("  <actor>\n    <name>{}</name>\n  </actor>\n", escape(name)));
+    }
+    if let Some(cover) = &d.cover_url {
+        out.push_str(&format!("  <thumb aspect=\"poster\">{}</thumb>\n", escape(cover)));
+        out.push_str(&format!("  <fanart>\n    <thumb>{}</thumb>\n  </fanart>\n", escape(cover)));
+    }
+    out.push_str("</movie>\n");
+    out
+}
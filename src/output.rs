@@ -0,0 +1,317 @@
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use schemars::JsonSchema;
+use serde::Serialize;
+use serde_json::{Map, Value};
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+
+use av::sources::dmm::Entry;
+use av::{i18n, ActorPage, AmbiguousMatch, AvDetail, AvItem, MagnetInfo, ReviewPage};
+
+use crate::doctor::Check;
+
+/// Version of the `json`/`yaml` envelope and the types inside it; bumped on breaking changes.
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(ValueEnum, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Format {
+    // Human readable tables
+    #[default]
+    Table,
+    Json,
+    // One JSON object per line
+    Jsonl,
+    Csv,
+    Yaml,
+    Markdown,
+    // Each record rendered through `--template`
+    Template,
+}
+
+/// What the `data` of an envelope holds; `av schema <kind>` prints its JSON Schema.
+#[derive(ValueEnum, Clone, Copy, Debug, PartialEq, Eq, Serialize, JsonSchema)]
+#[serde(rename_all = "lowercase")]
+pub enum Kind {
+    Detail,
+    Items,
+    Actors,
+    Entries,
+    Ambiguous,
+    Checks,
+    Reviews,
+    Magnets,
+    File,
+    Url,
+}
+
+/// Magnets of a title (`av install`), best seeded first.
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Magnets {
+    pub code: String,
+    pub title: String,
+    pub magnets: Vec<MagnetInfo>,
+    /// Bare magnet links, when no source gave details
+    pub links: Vec<String>,
+}
+
+/// A file written by the command (`av trailer`).
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct File {
+    pub path: PathBuf,
+}
+
+/// A URL opened by the command (`av view`).
+#[derive(Debug, Serialize, JsonSchema)]
+pub struct Url {
+    pub url: String,
+}
+
+/// Document printed by `--format json` and `yaml`. Record formats (jsonl, csv,
+/// markdown, template) print the records of `data` only.
+#[derive(Debug, Serialize, JsonSchema)]
+struct Envelope<T> {
+    schema_version: u32,
+    kind: Kind,
+    /// Null when the command failed
+    data: Option<T>,
+    errors: Vec<ErrorInfo>,
+    meta: Meta,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+struct ErrorInfo {
+    message: String,
+}
+
+#[derive(Debug, Serialize, JsonSchema)]
+struct Meta {
+    /// Version of av that produced the output
+    version: String,
+    /// Number of records when `data` is a list
+    count: Option<usize>,
+}
+
+impl<T> Envelope<T> {
+    fn new(kind: Kind, data: Option<T>, errors: Vec<ErrorInfo>, count: Option<usize>) -> Self {
+        let meta = Meta { version: env!("CARGO_PKG_VERSION").to_string(), count };
+        Self { schema_version: SCHEMA_VERSION, kind, data, errors, meta }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Options {
+    format: Format,
+    fields: Vec<String>,
+    template: Option<String>,
+}
+
+static OPTIONS: OnceLock<Options> = OnceLock::new();
+
+/// Global output options from the CLI. `template` is either the template itself or a path to a file holding it.
+pub fn set_options(format: Format, fields: Option<&str>, template: Option<&str>) -> Result<()> {
+    let fields = fields
+        .map(|f| f.split(',').map(|c| c.trim().to_string()).filter(|c| !c.is_empty()).collect())
+        .unwrap_or_default();
+    let template = match template {
+        Some(t) if Path::new(t).is_file() => {
+            Some(std::fs::read_to_string(t).with_context(|| i18n::tf("err.read_template", &[&t]))?)
+        }
+        Some(t) => Some(t.to_string()),
+        None => None,
+    };
+    if format == Format::Template && template.is_none() {
+        anyhow::bail!(i18n::t("err.template_missing"));
+    }
+    let _ = OPTIONS.set(Options { format, fields, template });
+    Ok(())
+}
+
+fn options() -> &'static Options {
+    OPTIONS.get_or_init(Options::default)
+}
+
+pub fn format() -> Format {
+    options().format
+}
+
+/// Anything other than the human tables.
+pub fn is_structured() -> bool {
+    format() != Format::Table
+}
+
+/// `json` and `yaml`, which wrap their output in the versioned envelope.
+pub fn is_enveloped() -> bool {
+    matches!(format(), Format::Json | Format::Yaml)
+}
+
+/// Print `value` in the selected machine-readable format, applying `--fields`.
+/// Arrays become one record per element, anything else a single record.
+pub fn print<T: Serialize>(kind: Kind, value: &T) -> Result<()> {
+    let opts = options();
+    let value = serde_json::to_value(value)?;
+    let single = !value.is_array();
+    let mut records = match value {
+        Value::Array(items) => items,
+        other => vec![other],
+    };
+    if !opts.fields.is_empty() {
+        records = records.iter().map(|r| project(r, &opts.fields)).collect();
+    }
+    match opts.format {
+        Format::Table | Format::Json => println!("{}", serde_json::to_string_pretty(&envelope(kind, records, single))?),
+        Format::Jsonl => {
+            for r in &records {
+                println!("{}", serde_json::to_string(r)?);
+            }
+        }
+        Format::Yaml => print!("{}", serde_yaml::to_string(&envelope(kind, records, single))?),
+        Format::Csv => print!("{}", render_csv(&records, &opts.fields)?),
+        Format::Markdown => print!("{}", render_markdown(&records, &opts.fields)),
+        Format::Template => {
+            let source = opts.template.as_deref().unwrap_or_default();
+            for line in render_template(source, &records)? {
+                println!("{}", line);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn envelope(kind: Kind, mut records: Vec<Value>, single: bool) -> Envelope<Value> {
+    if single {
+        Envelope::new(kind, records.pop(), Vec::new(), None)
+    } else {
+        let count = records.len();
+        Envelope::new(kind, Some(Value::Array(records)), Vec::new(), Some(count))
+    }
+}
+
+/// Report a failed command as an envelope with `errors` set. Only `json` and `yaml`
+/// have somewhere to put it; other formats leave the error to stderr alone.
+pub fn print_failure(kind: Kind, err: &anyhow::Error) -> Result<()> {
+    let errors = vec![ErrorInfo { message: format!("{:#}", err) }];
+    // Ambiguous codes carry the candidates to choose from
+    let doc = match err.downcast_ref::<AmbiguousMatch>() {
+        Some(ambiguous) => Envelope::new(Kind::Ambiguous, Some(serde_json::to_value(ambiguous)?), errors, None),
+        None => Envelope::new(kind, None, errors, None),
+    };
+    match format() {
+        Format::Json => println!("{}", serde_json::to_string_pretty(&doc)?),
+        Format::Yaml => print!("{}", serde_yaml::to_string(&doc)?),
+        _ => {}
+    }
+    Ok(())
+}
+
+/// JSON Schema of the envelope holding `kind`.
+pub fn print_schema(kind: Kind) -> Result<()> {
+    let schema = match kind {
+        Kind::Detail => schemars::schema_for!(Envelope<AvDetail>),
+        Kind::Items => schemars::schema_for!(Envelope<Vec<AvItem>>),
+        Kind::Actors => schemars::schema_for!(Envelope<ActorPage>),
+        Kind::Entries => schemars::schema_for!(Envelope<Vec<Entry>>),
+        Kind::Ambiguous => schemars::schema_for!(Envelope<AmbiguousMatch>),
+        Kind::Checks => schemars::schema_for!(Envelope<Vec<Check>>),
+        Kind::Reviews => schemars::schema_for!(Envelope<ReviewPage>),
+        Kind::Magnets => schemars::schema_for!(Envelope<Magnets>),
+        Kind::File => schemars::schema_for!(Envelope<File>),
+        Kind::Url => schemars::schema_for!(Envelope<Url>),
+    };
+    println!("{}", serde_json::to_string_pretty(&schema)?);
+    Ok(())
+}
+
+/// Keep only `fields` (in that order); dotted names reach into nested values, e.g. `magnet_infos.0.url`.
+fn project(record: &Value, fields: &[String]) -> Value {
+    let mut out = Map::new();
+    for f in fields {
+        let pointer = format!("/{}", f.replace('.', "/"));
+        out.insert(f.clone(), record.pointer(&pointer).cloned().unwrap_or(Value::Null));
+    }
+    Value::Object(out)
+}
+
+/// `--fields` if given, otherwise every key in order of first appearance.
+fn columns(records: &[Value], fields: &[String]) -> Vec<String> {
+    if !fields.is_empty() {
+        return fields.to_vec();
+    }
+    let mut cols: Vec<String> = Vec::new();
+    for r in records {
+        match r {
+            Value::Object(map) => {
+                for k in map.keys() {
+                    if !cols.contains(k) { cols.push(k.clone()); }
+                }
+            }
+            _ if !cols.iter().any(|c| c == "value") => cols.push("value".to_string()),
+            _ => {}
+        }
+    }
+    cols
+}
+
+fn cell(record: &Value, column: &str) -> String {
+    let v = match record {
+        Value::Object(map) => map.get(column).unwrap_or(&Value::Null),
+        other if column == "value" => other,
+        _ => &Value::Null,
+    };
+    flatten(v)
+}
+
+/// Scalars as text, lists of scalars joined with `; `, anything deeper as compact JSON.
+fn flatten(v: &Value) -> String {
+    match v {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        Value::Bool(_) | Value::Number(_) => v.to_string(),
+        Value::Array(items) if items.iter().all(|i| !i.is_array() && !i.is_object()) => {
+            items.iter().map(flatten).collect::<Vec<_>>().join("; ")
+        }
+        _ => v.to_string(),
+    }
+}
+
+fn render_csv(records: &[Value], fields: &[String]) -> Result<String> {
+    let cols = columns(records, fields);
+    let mut w = csv::Writer::from_writer(Vec::new());
+    w.write_record(&cols)?;
+    for r in records {
+        w.write_record(cols.iter().map(|c| cell(r, c)))?;
+    }
+    Ok(String::from_utf8(w.into_inner()?)?)
+}
+
+fn render_markdown(records: &[Value], fields: &[String]) -> String {
+    let cols = columns(records, fields);
+    let escape = |s: String| s.replace('|', "\\|").replace('\n', "<br>");
+    let mut out = format!("| {} |\n", cols.join(" | "));
+    out.push_str(&format!("|{}\n", " --- |".repeat(cols.len())));
+    for r in records {
+        let cells: Vec<String> = cols.iter().map(|c| escape(cell(r, c))).collect();
+        out.push_str(&format!("| {} |\n", cells.join(" | ")));
+    }
+    out
+}
+
+/// Render the template once per record. Fields are top-level variables and
+/// `index` is the 1-based record number, e.g. `{{ index }}. {{ code }} {{ title }}`.
+fn render_template(source: &str, records: &[Value]) -> Result<Vec<String>> {
+    let mut env = minijinja::Environment::new();
+    env.add_template("output", source).with_context(|| i18n::t("err.template"))?;
+    let tmpl = env.get_template("output")?;
+    records
+        .iter()
+        .enumerate()
+        .map(|(i, r)| {
+            let mut ctx = match r {
+                Value::Object(map) => map.clone(),
+                other => Map::from_iter([("value".to_string(), other.clone())]),
+            };
+            ctx.insert("index".to_string(), Value::from(i + 1));
+            tmpl.render(&ctx).with_context(|| i18n::t("err.template"))
+        })
+        .collect()
+}
@@ -1,25 +1,28 @@
-use anyhow::{Context, Result};
+use anyhow::Result;
 use regex::Regex;
-use reqwest::header::{HeaderMap, HeaderValue, HeaderName, ACCEPT, ACCEPT_LANGUAGE, REFERER, USER_AGENT};
-use scraper::{Html, Selector};
+use reqwest::header::{HeaderMap, HeaderValue, HeaderName, ACCEPT, ACCEPT_LANGUAGE, REFERER};
+use scraper::Html;
 use urlencoding::encode;
 
-use crate::types::{AvDetail, AvItem, MagnetInfo, ActorItem};
-use std::collections::HashMap;
-use crate::sources::{dmm, javlibrary};
+use crate::breaker;
+use crate::classify::{Censorship, Classification};
+use crate::client::{self, Source};
+use crate::code::{self, CodeKey};
+use crate::i18n;
+use crate::mirrors;
+use crate::types::{actress_names, AvDetail, AvItem, MagnetInfo, ActorItem, CastMember, CastRole, Candidate, Date, Rating, RelatedItem, Relation, ReviewPage, Runtime};
+use serde::Serialize;
+use std::collections::{BTreeMap, HashMap};
+use crate::sources::{defs, dmm, javlibrary, reviews};
 use crate::util;
 
-const UA: &str =
-    "Mozilla/5.0 (Macintosh; Intel Mac OS X 10_15_7) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/125.0 Safari/537.36";
-
 fn default_headers() -> HeaderMap {
     let mut headers = HeaderMap::new();
-    headers.insert(USER_AGENT, HeaderValue::from_static(UA));
     headers.insert(ACCEPT, HeaderValue::from_static("text/html,application/xhtml+xml,application/xml;q=0.9,image/avif,image/webp,*/*;q=0.8"));
     headers.insert(ACCEPT_LANGUAGE, HeaderValue::from_static("en-US,en;q=0.9,ja;q=0.8,zh-CN;q=0.7"));
     let referer = format!("{}/", javdb_base());
     if let Ok(hv) = HeaderValue::from_str(&referer) { headers.insert(REFERER, hv); }
-    if let Some(cookie) = std::env::var("AV_JAVDB_COOKIE").ok() {
+    if let Some(cookie) = &client::settings().javdb_cookie {
         let name = HeaderName::from_static("cookie");
         if let Ok(val) = HeaderValue::from_str(cookie.trim()) {
             headers.insert(name, val);
@@ -29,71 +32,139 @@ fn default_headers() -> HeaderMap {
 }
 
 fn client() -> reqwest::Client {
-    let mut builder = reqwest::Client::builder()
+    client::settings()
+        .http_builder()
         .default_headers(default_headers())
         .redirect(reqwest::redirect::Policy::limited(10))
         .cookie_store(true)
-        ;
-    if let Some(proxy) = std::env::var("AV_HTTP_PROXY").ok() {
-        if let Ok(px) = reqwest::Proxy::all(proxy) { builder = builder.proxy(px); }
-    }
-    builder.build().expect("client build")
+        .build()
+        .expect("client build")
 }
 
+/// Preferred JavDB mirror, for headers and links not tied to a fetched page.
 fn javdb_base() -> String {
-    std::env::var("AV_JAVDB_BASE").unwrap_or_else(|_| "https://javdb.com".to_string())
+    mirrors::ordered(Source::JavDb).into_iter().next().unwrap_or_default()
+}
+
+/// `href` as an absolute URL on `base`.
+fn absolute(base: &str, href: &str) -> String {
+    if href.starts_with("http") { href.to_string() } else { format!("{}{}", base, href) }
+}
+
+/// Configured for this client and not cut off by an open circuit breaker.
+fn enabled(source: Source) -> bool {
+    client::settings().has(source) && breaker::allows(source)
+}
+
+/// Error out of a per-source lookup when the client was built without that source
+/// or its circuit breaker is open.
+fn require(source: Source) -> Result<()> {
+    if !client::settings().has(source) {
+        anyhow::bail!(i18n::tf("err.source_disabled", &[&format!("{:?}", source)]));
+    }
+    breaker::check(source)
+}
+
+/// Raised when a search returns several titles whose code equals the requested one.
+#[derive(Debug, Serialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[non_exhaustive]
+pub struct AmbiguousMatch {
+    pub code: String,
+    pub candidates: Vec<Candidate>,
+}
+
+impl std::fmt::Display for AmbiguousMatch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", i18n::tf("err.ambiguous", &[&self.code, &self.candidates.len()]))
+    }
+}
+
+impl std::error::Error for AmbiguousMatch {}
+
+/// Raised when no source has the requested title.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct NotFound {
+    pub message: String,
+}
+
+impl NotFound {
+    pub(crate) fn new(message: String) -> Self {
+        Self { message }
+    }
+}
+
+impl std::fmt::Display for NotFound {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for NotFound {}
+
+/// Raised for input no lookup can use: a string holding no code, an unsupported URL
+/// or a `pick` outside the candidate list.
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct BadRequest {
+    pub message: String,
+}
+
+impl BadRequest {
+    pub(crate) fn new(message: String) -> Self {
+        Self { message }
+    }
+}
+
+impl std::fmt::Display for BadRequest {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for BadRequest {}
+
+/// Choose among scored candidates: `pick` is a 1-based index into `candidates`,
+/// otherwise exactly one exact-code match is required.
+fn pick_candidate(code: &str, candidates: Vec<Candidate>, pick: Option<usize>) -> Result<Option<Candidate>> {
+    if let Some(n) = pick {
+        let total = candidates.len();
+        return candidates
+            .into_iter()
+            .nth(n.wrapping_sub(1))
+            .map(Some)
+            .ok_or_else(|| BadRequest::new(i18n::tf("err.pick_range", &[&n, &total])).into());
+    }
+    let exact = candidates.iter().filter(|c| c.score >= 1.0).count();
+    match exact {
+        0 => Ok(None),
+        1 => Ok(candidates.into_iter().find(|c| c.score >= 1.0)),
+        _ => Err(AmbiguousMatch { code: code.to_string(), candidates }.into()),
+    }
 }
 
 pub async fn fetch_detail(code: &str) -> Result<AvDetail> {
-    // Prefer JavDB native scraping by default; DMM is opt-in via env AV_USE_DMM=1
+    fetch_detail_pick(code, None).await
+}
+
+pub async fn fetch_detail_pick(code: &str, pick: Option<usize>) -> Result<AvDetail> {
+    // Prefer JavDB native scraping by default
     let code_upper = code.to_uppercase();
+    CodeKey::parse(code).ok_or_else(|| BadRequest::new(i18n::t("err.bad_code")))?;
     util::debug(format!("fetch_detail start for {}", code_upper));
-    if std::env::var("AV_USE_DMM").ok().as_deref() == Some("1") && dmm::dmm_enabled() {
-        if let Some(mut d) = dmm::fetch_detail_from_dmm(&code_upper).await? {
-            util::debug("DMM hit");
-            // Merge with JavDB for plot/actors/cover fallback
-            if let Ok(j) = fetch_detail_from_javdb(&code_upper).await {
-                util::debug("Merging with JavDB after DMM");
-                if d.plot.is_none() && j.plot.is_some() { d.plot = j.plot; }
-                if d.actor_names.is_empty() && !j.actor_names.is_empty() { d.actor_names = j.actor_names; }
-                if d.cover_url.is_none() && j.cover_url.is_some() { d.cover_url = j.cover_url; }
-                // Prefer DMM release_date/duration if present; else copy from JavDB
-                if d.release_date.is_none() { d.release_date = j.release_date; }
-                if d.duration_minutes.is_none() { d.duration_minutes = j.duration_minutes; }
-            }
-            // Always merge magnets from Sukebei
-            if let Ok(s) = fetch_detail_from_sukebei(&code_upper).await {
-                if d.magnets.is_empty() { d.magnets = s.magnets; }
-                if d.magnet_infos.is_empty() { d.magnet_infos = s.magnet_infos; }
-            }
-            return Ok(d);
-        }
-    }
-    if let Ok(mut detail) = fetch_detail_from_javdb(&code_upper).await {
-        util::debug("JavDB hit");
-        // Merge extra metadata from JavLibrary even when JavDB succeeds
-        if let Ok(Some(jl)) = javlibrary::fetch_detail_from_javlibrary(&code_upper).await {
-            util::debug("Merging with JavLibrary after JavDB");
-            if detail.plot.is_none() && jl.plot.is_some() { detail.plot = jl.plot; }
-            if detail.actor_names.is_empty() && !jl.actor_names.is_empty() { detail.actor_names = jl.actor_names; }
-            if detail.release_date.is_none() && jl.release_date.is_some() { detail.release_date = jl.release_date; }
-            if detail.cover_url.is_none() && jl.cover_url.is_some() { detail.cover_url = jl.cover_url; }
-            if detail.duration_minutes.is_none() && jl.duration_minutes.is_some() { detail.duration_minutes = jl.duration_minutes; }
-            if detail.director.is_none() && jl.director.is_some() { detail.director = jl.director; }
-            if detail.studio.is_none() && jl.studio.is_some() { detail.studio = jl.studio; }
-            if detail.label.is_none() && jl.label.is_some() { detail.label = jl.label; }
-            if detail.series.is_none() && jl.series.is_some() { detail.series = jl.series; }
-            if detail.genres.is_empty() && !jl.genres.is_empty() { detail.genres = jl.genres; }
-            if detail.preview_images.is_empty() && !jl.preview_images.is_empty() { detail.preview_images = jl.preview_images; }
-        }
-        if detail.magnets.is_empty() {
-            if let Ok(s_detail) = fetch_detail_from_sukebei(&code_upper).await {
-                if !s_detail.magnets.is_empty() {
-                    detail.magnets = s_detail.magnets;
-                }
-            }
+    if enabled(Source::Dmm) {
+        // A DMM failure is already recorded by its circuit breaker; fall back to the HTML sources.
+        match dmm::fetch_detail_from_dmm(&code_upper).await {
+            Ok(Some(d)) => return Ok(merge_after_dmm(d, &code_upper, pick).await),
+            Ok(None) => {}
+            Err(e) => util::debug(format!("DMM lookup failed, falling back: {:#}", e)),
         }
-        return Ok(detail);
+    }
+    match fetch_detail_from_javdb(&code_upper, pick).await {
+        Ok(detail) => return merge_after_javdb(&code_upper, detail).await,
+        Err(e) if e.is::<AmbiguousMatch>() || e.is::<BadRequest>() => return Err(e),
+        Err(e) => util::debug(format!("JavDB miss: {:#}", e)),
     }
     // Try JavLibrary
     if let Ok(Some(mut jl)) = javlibrary::fetch_detail_from_javlibrary(&code_upper).await {
@@ -108,14 +179,101 @@ pub async fn fetch_detail(code: &str) -> Result<AvDetail> {
     fetch_detail_from_sukebei(&code_upper).await
 }
 
+/// DMM `d` topped up with JavDB's plot, cast, reviews and cover and with Sukebei's magnets.
+async fn merge_after_dmm(mut d: AvDetail, code_upper: &str, pick: Option<usize>) -> AvDetail {
+    util::debug("DMM hit");
+    // Merge with JavDB for plot/actors/cover fallback
+    if let Ok(j) = fetch_detail_from_javdb(code_upper, pick).await {
+        util::debug("Merging with JavDB after DMM");
+        if d.plot.is_none() && j.plot.is_some() { d.plot = j.plot; }
+        if d.actor_names.is_empty() && !j.actor_names.is_empty() { d.actor_names = j.actor_names; }
+        d.merge_cast(j.cast);
+        d.merge_related(j.related);
+        if d.reviews.is_empty() { d.reviews = j.reviews; }
+        if d.score_distribution.is_empty() { d.score_distribution = j.score_distribution; }
+        if d.cover_url.is_none() && j.cover_url.is_some() { d.cover_url = j.cover_url; }
+        // Prefer DMM release_date/duration if present; else copy from JavDB
+        if d.release_date.is_none() { d.release_date = j.release_date; }
+        if d.duration.is_none() { d.duration = j.duration; }
+        merge_genres(&mut d.genres, j.genres);
+        d.merge_localized(j.localized);
+    }
+    // Always merge magnets from Sukebei
+    if let Ok(s) = fetch_detail_from_sukebei(code_upper).await {
+        if d.magnets.is_empty() { d.magnets = s.magnets; }
+        if d.magnet_infos.is_empty() { d.magnet_infos = s.magnet_infos; }
+    }
+    d
+}
+
+/// Detail for a title page URL: JavDB `/v/…` pages are parsed directly, DMM URLs
+/// resolve through their `cid`, anything else through a code found in the URL.
+pub async fn fetch_detail_by_url(url: &str) -> Result<AvDetail> {
+    if mirrors::owns(Source::JavDb, url) && url.contains("/v/") {
+        require(Source::JavDb)?;
+        let c = client();
+        let mut detail = fetch_javdb_detail(&c, url).await?;
+        add_javdb_locales(&c, url, &mut detail).await;
+        let code_upper = detail.code.to_uppercase();
+        return merge_after_javdb(&code_upper, detail).await;
+    }
+    let cid = Regex::new(r"cid=([a-z0-9_]+)").unwrap().captures(url).map(|c| dmm::content_id_to_code(&c[1]));
+    let code = cid.or_else(|| code::extract_code(url)).ok_or_else(|| BadRequest::new(i18n::tf("err.url_unsupported", &[&url])))?;
+    util::debug(format!("{} -> {}", url, code));
+    fetch_detail(&code).await
+}
+
+/// Add the tags of another source; the taxonomy unifies them into canonical genres later.
+fn merge_genres(into: &mut Vec<String>, more: Vec<String>) {
+    for g in more {
+        if !into.contains(&g) { into.push(g); }
+    }
+}
+
+async fn merge_after_javdb(code_upper: &str, mut detail: AvDetail) -> Result<AvDetail> {
+    util::debug("JavDB hit");
+    // Merge extra metadata from JavLibrary even when JavDB succeeds
+    if let Ok(Some(jl)) = javlibrary::fetch_detail_from_javlibrary(code_upper).await {
+        util::debug("Merging with JavLibrary after JavDB");
+        if detail.plot.is_none() && jl.plot.is_some() { detail.plot = jl.plot; }
+        if detail.actor_names.is_empty() && !jl.actor_names.is_empty() { detail.actor_names = jl.actor_names; }
+        detail.merge_cast(jl.cast);
+        if detail.rating.is_none() { detail.rating = jl.rating; }
+        if detail.release_date.is_none() && jl.release_date.is_some() { detail.release_date = jl.release_date; }
+        if detail.cover_url.is_none() && jl.cover_url.is_some() { detail.cover_url = jl.cover_url; }
+        if detail.duration.is_none() && jl.duration.is_some() { detail.duration = jl.duration; }
+        if detail.director.is_none() && jl.director.is_some() { detail.director = jl.director; }
+        if detail.studio.is_none() && jl.studio.is_some() { detail.studio = jl.studio; }
+        if detail.label.is_none() && jl.label.is_some() { detail.label = jl.label; }
+        if detail.series.is_none() && jl.series.is_some() { detail.series = jl.series; }
+        merge_genres(&mut detail.genres, jl.genres);
+        detail.merge_localized(jl.localized);
+        if detail.preview_images.is_empty() && !jl.preview_images.is_empty() { detail.preview_images = jl.preview_images; }
+    }
+    if detail.magnets.is_empty() {
+        if let Ok(s_detail) = fetch_detail_from_sukebei(code_upper).await {
+            if !s_detail.magnets.is_empty() {
+                detail.magnets = s_detail.magnets;
+            }
+        }
+    }
+    Ok(detail)
+}
+
 pub async fn search(query: &str) -> Result<Vec<AvItem>> {
     let q = query.trim();
     if looks_like_code(q) {
         if let Ok(detail) = fetch_detail(q).await {
-            return Ok(vec![AvItem { code: detail.code, title: detail.title }]);
+            return Ok(vec![AvItem::new(detail.code, detail.title)]);
         }
     }
-    let mut items = search_javdb(q).await.unwrap_or_default();
+    let mut items = Vec::new();
+    if enabled(Source::Dmm) {
+        items = dmm::search(q, 1, 50).await.unwrap_or_default();
+    }
+    if items.is_empty() {
+        items = search_javdb(q).await.unwrap_or_default();
+    }
     if items.is_empty() {
         items = search_sukebei(q).await.unwrap_or_default();
     }
@@ -123,275 +281,396 @@ pub async fn search(query: &str) -> Result<Vec<AvItem>> {
 }
 
 pub async fn list_actor_titles(actor: &str) -> Result<Vec<AvItem>> {
-    let mut items = list_actor_javdb(actor).await.unwrap_or_default();
+    let mut items = Vec::new();
+    if enabled(Source::Dmm) {
+        items = dmm::list_actress_titles(actor).await.unwrap_or_default();
+    }
+    if items.is_empty() {
+        items = list_actor_javdb(actor).await.unwrap_or_default();
+    }
     if items.is_empty() {
         items = list_actor_sukebei(actor).await.unwrap_or_default();
     }
     Ok(items)
 }
 
+/// Detail of `code` from `source` alone, without merging other sources.
+pub async fn fetch_detail_from(source: Source, code: &str) -> Result<Option<AvDetail>> {
+    require(source)?;
+    let code_upper = code.to_uppercase();
+    match source {
+        Source::Dmm => dmm::fetch_detail_from_dmm(&code_upper).await,
+        Source::JavDb => fetch_detail_from_javdb(&code_upper, None).await.map(Some),
+        Source::JavLibrary => javlibrary::fetch_detail_from_javlibrary(&code_upper).await,
+        Source::Sukebei => fetch_detail_from_sukebei(&code_upper).await.map(Some),
+    }
+}
+
+/// Titles of `actor` from `source` alone; JavLibrary has no actor listing.
+pub async fn list_actor_titles_from(source: Source, actor: &str) -> Result<Vec<AvItem>> {
+    require(source)?;
+    match source {
+        Source::Dmm => dmm::list_actress_titles(actor).await,
+        Source::JavDb => list_actor_javdb(actor).await,
+        Source::JavLibrary => Ok(Vec::new()),
+        Source::Sukebei => list_actor_sukebei(actor).await,
+    }
+}
+
+/// HTTP status of `source`'s front page (for DMM, of its API).
+pub async fn ping(source: Source) -> Result<u16> {
+    require(source)?;
+    if source == Source::Dmm { return dmm::ping().await; }
+    let d = defs::get(source.name());
+    let page = mirrors::fetch(&client(), source, |base| d.url("home", &[("base", base)])).await?;
+    util::debug(format!("{:?} answered from {}", source, page.base));
+    Ok(page.status)
+}
+
 fn looks_like_code(s: &str) -> bool {
     let re = Regex::new(r"(?i)^[a-z]{2,5}-?\d{2,5}").unwrap();
     re.is_match(s)
 }
 
 pub async fn top(limit: usize) -> Result<Vec<AvItem>> {
+    if enabled(Source::Dmm) {
+        let items = dmm::latest(limit).await.unwrap_or_default();
+        if !items.is_empty() { return Ok(items); }
+    }
+    if !enabled(Source::JavDb) { return Ok(Vec::new()); }
     // Try multiple ordering pages on JavDB: most recent, trending, etc.
     let c = client();
+    let d = defs::get("javdb");
     let mut items: Vec<AvItem> = Vec::new();
-    let endpoints = [
-        format!("{}/videos?o=mr", javdb_base()), // most recent
-        format!("{}/videos?o=tr", javdb_base()), // trending
-    ];
-    let card_sel = Selector::parse(".movie-list .item a.box.cover, .movie-list a[href^='/v/'], a.box[href^='/v/']").unwrap();
-    let title_sel = Selector::parse(".video-title").unwrap();
-    for url in &endpoints {
-        util::debug(format!("JavDB top page: {}", url));
-        let body = c.get(url).send().await?.error_for_status()?.text().await?;
-        let doc = Html::parse_document(&body);
-        for a in doc.select(&card_sel) {
-            let href = a.value().attr("href").unwrap_or("");
-            let title = a.select(&title_sel).next().map(|n| n.text().collect::<String>()).unwrap_or_else(|| a.text().collect::<String>());
-            let code = extract_code_from_title(&title).unwrap_or_else(|| href.split('/').last().unwrap_or("").to_string());
-            if !code.is_empty() && !title.is_empty() {
-                items.push(AvItem { code: code.to_uppercase(), title });
-                if items.len() >= limit { return Ok(items); }
-            }
+    for template in d.templates("latest") {
+        util::debug(format!("JavDB top page: {}", template));
+        let body = mirrors::fetch(&c, Source::JavDb, |base| defs::fill(template, &[("base", base)])).await?.body;
+        for item in parse_javdb_cards(&body) {
+            items.push(item);
+            if items.len() >= limit { return Ok(items); }
         }
     }
     Ok(items)
 }
 
-async fn fetch_detail_from_javdb(code: &str) -> Result<AvDetail> {
+/// Title cards of a JavDB listing or search page.
+fn parse_javdb_cards(body: &str) -> Vec<AvItem> {
+    let doc = Html::parse_document(body);
+    let d = defs::get("javdb");
+    let title_sel = d.sel("card_title");
+    let mut items = Vec::new();
+    for a in doc.select(d.sel("card")) {
+        let href = a.value().attr("href").unwrap_or("");
+        let title = a.select(title_sel).next().map(|n| n.text().collect::<String>()).unwrap_or_else(|| a.text().collect::<String>());
+        let code = extract_code_from_title(&title).unwrap_or_else(|| href.split('/').next_back().unwrap_or("").to_string());
+        if !code.is_empty() && !title.is_empty() {
+            items.push(AvItem::new(code.to_uppercase(), title));
+        }
+    }
+    items
+}
+
+async fn fetch_detail_from_javdb(code: &str, pick: Option<usize>) -> Result<AvDetail> {
+    require(Source::JavDb)?;
     let c = client();
-    let url = format!("{}/search?q={}&f=all", javdb_base(), encode(code));
+    let (url, direct) = javdb_detail_url(&c, code, pick).await?;
+    let mut detail = fetch_javdb_detail(&c, &url).await?;
+    if direct && !detail.code.is_empty() && !CodeKey::parse(code).is_some_and(|k| k.matches(&detail.code)) {
+        anyhow::bail!(i18n::tf("err.code_mismatch", &[&"JavDB", &detail.code, &code]));
+    }
+    add_javdb_locales(&c, &url, &mut detail).await;
+    Ok(detail)
+}
+
+/// Page with the detail of `code`, and whether the search rendered it directly (as
+/// searches for an exact code may); the title's own URL is used when that page names it.
+async fn javdb_detail_url(c: &reqwest::Client, code: &str, pick: Option<usize>) -> Result<(String, bool)> {
+    let d = defs::get("javdb");
+    let search_url = |base: &str| d.url("search", &[("base", base), ("query", &encode(code))]);
+    let page = mirrors::fetch(c, Source::JavDb, search_url).await?;
+    let url = search_url(&page.base);
     util::debug(format!("JavDB search: {}", url));
-    let body = c.get(&url).send().await?.error_for_status()?.text().await?;
-    let doc = Html::parse_document(&body);
-    // If search redirected or rendered directly to detail page
-    if doc.select(&Selector::parse(".video-meta-panel").unwrap()).next().is_some() {
+    let Some(candidates) = parse_javdb_search(&page.body, &page.base, code) else {
         util::debug("JavDB: search rendered detail page directly");
-        return parse_javdb_detail(&c, &url).await;
-    }
-    // Try several selectors to find the first result link
-    let candidates = [
-        ".movie-list .item a.box.cover",
-        ".movie-list a[href^='/v/']",
-        "a.box[href^='/v/']",
-        "a[href^='/v/']",
-    ];
-    let mut href: Option<String> = None;
-    for sel in candidates {
-        let s = Selector::parse(sel).unwrap();
-        if let Some(a) = doc.select(&s).next() {
-            if let Some(h) = a.value().attr("href") {
-                href = Some(h.to_string());
-                util::debug(format!("JavDB: picked result via selector '{}' => {}", sel, h));
-                break;
+        return Ok((parse_javdb_canonical(&page.body, &page.base).unwrap_or(url), true));
+    };
+    util::debug(format!("JavDB: {} candidate(s) for {}", candidates.len(), code));
+    let picked = pick_candidate(code, candidates, pick)?.ok_or_else(|| NotFound::new(i18n::tf("err.not_found", &[&"JavDB"])))?;
+    util::debug(format!("JavDB detail: {} ({}, score {:.2})", picked.url, picked.code, picked.score));
+    Ok((picked.url, false))
+}
+
+/// Scored result cards of a JavDB search page, or `None` when the page is the detail itself.
+fn parse_javdb_search(body: &str, base: &str, code: &str) -> Option<Vec<Candidate>> {
+    let doc = Html::parse_document(body);
+    if doc.select(defs::get("javdb").sel("detail_page")).next().is_some() { return None; }
+    Some(javdb_candidates(&doc, base, code))
+}
+
+/// The `/v/…` URL a detail page gives for itself.
+fn parse_javdb_canonical(body: &str, base: &str) -> Option<String> {
+    let doc = Html::parse_document(body);
+    doc.select(defs::get("javdb").sel("canonical"))
+        .filter_map(|n| n.value().attr("href").or_else(|| n.value().attr("content")))
+        .find(|href| href.contains("/v/"))
+        .map(|href| absolute(base, href))
+}
+
+/// Page `page` of the reviews of `code` on JavDB and JavLibrary, with the rating
+/// and its breakdown from the title pages.
+pub async fn reviews(code: &str, page: usize, pick: Option<usize>) -> Result<ReviewPage> {
+    let mut out = ReviewPage { code: code.to_uppercase(), rating: None, score_distribution: BTreeMap::new(), reviews: Vec::new(), page };
+    let mut found = false;
+    if enabled(Source::JavDb) {
+        match javdb_reviews(code, page, pick).await {
+            Ok(detail) => {
+                found = true;
+                out.rating = detail.rating;
+                out.score_distribution = detail.score_distribution;
+                out.reviews = detail.reviews;
             }
+            Err(e) if e.is::<AmbiguousMatch>() => return Err(e),
+            Err(e) => util::debug(format!("JavDB reviews failed: {:#}", e)),
+        }
+    }
+    match javlibrary::fetch_reviews(code, page).await {
+        Ok(Some((rating, reviews))) => {
+            found = true;
+            if out.rating.is_none() { out.rating = rating; }
+            out.reviews.extend(reviews);
+        }
+        Ok(None) => {}
+        Err(e) => util::debug(format!("JavLibrary reviews failed: {:#}", e)),
+    }
+    if !found { return Err(NotFound::new(i18n::tf("err.not_found", &[&"JavDB/JavLibrary"])).into()); }
+    Ok(out)
+}
+
+/// The title page's rating, breakdown and reviews, the reviews replaced by page
+/// `page` of the full list.
+async fn javdb_reviews(code: &str, page: usize, pick: Option<usize>) -> Result<AvDetail> {
+    let c = client();
+    let d = defs::get("javdb");
+    let (url, direct) = javdb_detail_url(&c, code, pick).await?;
+    let mut detail = fetch_javdb_detail(&c, &url).await?;
+    // A search page has no review list under it, only the title's own URL does
+    if !direct || url.contains("/v/") {
+        let list_url = d.url("reviews", &[("url", &url), ("page", &page.to_string())]);
+        let body = mirrors::fetch_url(&c, Source::JavDb, &list_url).await?.body;
+        detail.reviews = reviews::parse(&Html::parse_document(&body), "javdb");
+    } else if page > 1 {
+        detail.reviews.clear();
+    }
+    Ok(detail)
+}
+
+/// File the page language's metadata under [`AvDetail::localized`] and add the
+/// other requested languages JavDB offers; a locale that fails is left out.
+async fn add_javdb_locales(c: &reqwest::Client, url: &str, detail: &mut AvDetail) {
+    let d = defs::get("javdb");
+    let native = d.language.clone().unwrap_or_default();
+    detail.localized.insert(native.clone(), detail.snapshot());
+    let sep = if url.contains('?') { "&" } else { "?" };
+    for lang in &client::settings().languages {
+        let tag = lang.tag();
+        let Some(locale) = d.locales.get(tag).filter(|_| tag != native) else { continue };
+        match fetch_javdb_detail(c, &d.url("localized", &[("url", url), ("sep", sep), ("locale", locale)])).await {
+            Ok(variant) => { detail.localized.insert(tag.to_string(), variant.snapshot()); }
+            Err(e) => util::debug(format!("JavDB ({}) failed: {:#}", tag, e)),
         }
     }
-    let href = href.context("JavDB 未找到该番号")?;
-    let detail_url = if href.starts_with("http") { href.to_string() } else { format!("{}{}", javdb_base(), href) };
-    util::debug(format!("JavDB detail: {}", detail_url));
-    parse_javdb_detail(&c, &detail_url).await
+}
+
+/// Result cards of a JavDB search page served by `base`, scored against `code`, best first.
+fn javdb_candidates(doc: &Html, base: &str, code: &str) -> Vec<Candidate> {
+    let Some(wanted) = CodeKey::parse(code) else { return Vec::new() };
+    let d = defs::get("javdb");
+    let card_sel = d.sel("candidate");
+    let code_sel = d.sel("card_code");
+    let title_sel = d.sel("card_title");
+    let mut candidates: Vec<Candidate> = Vec::new();
+    for a in doc.select(card_sel) {
+        let Some(href) = a.value().attr("href") else { continue };
+        let url = absolute(base, href);
+        if candidates.iter().any(|c| c.url == url) { continue; }
+        let title = a
+            .select(title_sel)
+            .next()
+            .map(|n| n.text().collect::<String>())
+            .unwrap_or_else(|| a.text().collect::<String>())
+            .trim()
+            .to_string();
+        let card_code = a
+            .select(code_sel)
+            .next()
+            .map(|n| n.text().collect::<String>().trim().to_uppercase())
+            .filter(|s| !s.is_empty())
+            .or_else(|| extract_code_from_title(&title))
+            .unwrap_or_default();
+        let score = wanted.score(&card_code);
+        if score <= 0.0 { continue; }
+        candidates.push(Candidate { code: card_code, title, source: "javdb".to_string(), url, score });
+    }
+    // Stable sort keeps the site's own ranking among equal scores
+    candidates.sort_by(|a, b| b.score.total_cmp(&a.score));
+    candidates
 }
 
 pub async fn get_play_url(code: &str) -> Result<String> {
+    require(Source::JavDb)?;
     let c = client();
-    let url = format!("{}/search?q={}&f=all", javdb_base(), encode(code));
+    let d = defs::get("javdb");
+    let search_url = |base: &str| d.url("search", &[("base", base), ("query", &encode(code))]);
+    let page = mirrors::fetch(&c, Source::JavDb, search_url).await?;
+    let url = search_url(&page.base);
     util::debug(format!("JavDB search for play: {}", url));
-    let body = c.get(&url).send().await?.error_for_status()?.text().await?;
-    let doc = Html::parse_document(&body);
-    
+
     // If search redirected or rendered directly to detail page
-    let play_sel = Selector::parse(".cover-container[href*='play'], a.cover-container[href*='play'], a[href*='play']").unwrap();
-    if let Some(play) = doc.select(&play_sel).next().and_then(|a| a.value().attr("href")) {
-        let play_url = if play.starts_with("http") { play.to_string() } else { format!("{}{}", javdb_base(), play) };
+    if let Some(play_url) = parse_javdb_play_link(&page.body, &page.base) {
         util::debug(format!("JavDB play URL: {}", play_url));
         return Ok(play_url);
     }
-    
-    // Try to get detail page first, then look for play link
-    let candidates = [
-        ".movie-list .item a.box.cover",
-        ".movie-list a[href^='/v/']",
-        "a.box[href^='/v/']",
-        "a[href^='/v/']",
-    ];
-    let mut href: Option<String> = None;
-    for sel in candidates {
-        let s = Selector::parse(sel).unwrap();
-        if let Some(a) = doc.select(&s).next() {
-            if let Some(h) = a.value().attr("href") {
-                href = Some(h.to_string());
-                break;
-            }
-        }
-    }
-    
-    if let Some(href) = href {
-        let detail_url = if href.starts_with("http") { href } else { format!("{}{}", javdb_base(), href) };
-        let detail_body = c.get(&detail_url).send().await?.error_for_status()?.text().await?;
-        let detail_doc = Html::parse_document(&detail_body);
-        
-        // Look for play button on detail page
-        if let Some(play) = detail_doc.select(&play_sel).next().and_then(|a| a.value().attr("href")) {
-            let play_url = if play.starts_with("http") { play.to_string() } else { format!("{}{}", javdb_base(), play) };
+
+    // Go through the first exact-code result's detail page, then look for play link
+    let exact = parse_javdb_search(&page.body, &page.base, code).unwrap_or_default().into_iter().find(|c| c.score >= 1.0);
+    if let Some(candidate) = exact {
+        let detail_page = mirrors::fetch_url(&c, Source::JavDb, &candidate.url).await?;
+        if let Some(play_url) = parse_javdb_play_link(&detail_page.body, &detail_page.base) {
             util::debug(format!("JavDB play URL from detail: {}", play_url));
             return Ok(play_url);
         }
     }
-    
+
     // Fallback: just return the search URL
     Ok(url)
 }
 
-async fn parse_javdb_detail(c: &reqwest::Client, url: &str) -> Result<AvDetail> {
-    let body = c.get(url).send().await?.error_for_status()?.text().await?;
-    let doc = Html::parse_document(&body);
-    let title_sel = Selector::parse(".title strong, h2.title").unwrap();
+/// Play button target of a JavDB page served by `base`.
+fn parse_javdb_play_link(body: &str, base: &str) -> Option<String> {
+    let doc = Html::parse_document(body);
+    let play = doc.select(defs::get("javdb").sel("play_link")).next()?.value().attr("href")?;
+    Some(absolute(base, play))
+}
+
+async fn fetch_javdb_detail(c: &reqwest::Client, url: &str) -> Result<AvDetail> {
+    let body = mirrors::fetch_url(c, Source::JavDb, url).await?.body;
+    Ok(parse_javdb_detail(&body))
+}
+
+/// Everything a JavDB title page shows, with magnets from its raw HTML.
+fn parse_javdb_detail(body: &str) -> AvDetail {
+    let doc = Html::parse_document(body);
+    let d = defs::get("javdb");
     let title = doc
-        .select(&title_sel)
+        .select(d.sel("title"))
         .next()
         .map(|n| n.text().collect::<String>())
         .unwrap_or_else(|| {
-            doc.select(&Selector::parse("title").unwrap())
+            doc.select(d.sel("title_fallback"))
                 .next()
                 .map(|n| n.text().collect::<String>())
                 .unwrap_or_default()
         });
 
-    let meta_sel = Selector::parse(".panel-block .value").unwrap();
     let mut code = String::new();
-    let mut date: Option<String> = None;
-    for val in doc.select(&meta_sel) {
+    let mut date: Option<Date> = None;
+    for val in doc.select(d.sel("meta_value")) {
         let txt = val.text().collect::<String>().trim().to_string();
         if code.is_empty() && looks_like_code(&txt) { code = txt.to_uppercase(); }
-        if txt.contains('-') && txt.len() == 10 && txt.chars().nth(4) == Some('-') { date = Some(txt); }
+        if let Some(found) = Date::find(&txt) { date = Some(found); }
     }
 
-    let cover_sel = Selector::parse("img.video-cover, .video-cover img").unwrap();
     let mut cover_url = doc
-        .select(&cover_sel)
+        .select(d.sel("cover"))
         .next()
         .and_then(|n| n.value().attr("src"))
         .map(|s| s.to_string());
     if cover_url.is_none() {
-        let og_sel = Selector::parse("meta[property='og:image']").unwrap();
         cover_url = doc
-            .select(&og_sel)
+            .select(d.sel("og_image"))
             .next()
             .and_then(|n| n.value().attr("content"))
             .map(|s| s.to_string());
     }
 
-    let actor_sel = Selector::parse(".panel-block a[href*='/actors/'], a[href*='/actors/']").unwrap();
-    let mut actor_names = doc
-        .select(&actor_sel)
-        .map(|n| n.text().collect::<String>().trim().to_string())
-        .filter(|s| !s.is_empty())
-        .collect::<Vec<_>>();
-
     // Init advanced fields before filling (must be declared before panel parsing loop)
-    let mut duration_minutes: Option<u32> = None;
+    let mut duration: Option<Runtime> = None;
     let mut director: Option<String> = None;
     let mut studio: Option<String> = None;
     let mut label: Option<String> = None;
     let mut series: Option<String> = None;
     let mut genres: Vec<String> = Vec::new();
-    let mut rating: Option<f32> = None;
+    let mut rating: Option<Rating> = None;
+    let mut cast: Vec<CastMember> = Vec::new();
 
     // Parse structured blocks in the movie info panel
-    let block_sel = Selector::parse("nav.panel.movie-panel-info .panel-block").unwrap();
-    let strong_sel = Selector::parse("strong").unwrap();
-    let value_sel = Selector::parse(".value").unwrap();
-    for bl in doc.select(&block_sel) {
+    let strong_sel = d.sel("info_label");
+    let value_sel = d.sel("info_value");
+    let link_sel = d.sel("info_link");
+    for bl in doc.select(d.sel("info_block")) {
         let label_text = bl
-            .select(&strong_sel)
+            .select(strong_sel)
             .next()
             .map(|n| n.text().collect::<String>())
-            .unwrap_or_default()
-            .to_lowercase();
-        let value_node = bl.select(&value_sel).next();
+            .unwrap_or_default();
+        let value_node = bl.select(value_sel).next();
         let value_text = value_node
             .as_ref()
             .map(|n| n.text().collect::<String>().trim().to_string())
             .unwrap_or_default();
+        let links = value_node
+            .as_ref()
+            .map(|n| n.select(link_sel).map(|a| a.text().collect::<String>().trim().to_string()).filter(|s| !s.is_empty()).collect::<Vec<_>>())
+            .unwrap_or_default();
 
-        if label_text.contains("id") && code.is_empty() {
+        if d.label_is("code", &label_text) && code.is_empty() {
             let raw = value_text.replace('\n', " ");
             let raw = raw.trim();
             if looks_like_code(raw) { code = raw.to_uppercase(); }
         }
-        if label_text.contains("released") {
-            if !value_text.is_empty() { date = Some(value_text.clone()); }
+        if d.label_is("date", &label_text) {
+            if let Some(found) = Date::find(&value_text) { date = Some(found); }
         }
-        if label_text.contains("duration") {
-            if let Some(m) = Regex::new(r"(\d{2,3})").unwrap().captures(&value_text).and_then(|c| c.get(1)).and_then(|m| m.as_str().parse::<u32>().ok()) {
-                duration_minutes = Some(m);
-            }
+        if d.label_is("duration", &label_text) {
+            if let Some(m) = d.capture("minutes", &value_text) { duration = Runtime::from_minutes(m); }
         }
-        if label_text.contains("director") {
-            if let Some(a) = value_node.as_ref().and_then(|n| n.select(&Selector::parse("a").unwrap()).next()) {
-                let name = a.text().collect::<String>().trim().to_string();
-                if !name.is_empty() { director = Some(name); }
-            }
+        if d.label_is("director", &label_text) {
+            if let Some(name) = links.first() { director = Some(name.clone()); }
         }
-        if label_text.contains("maker") {
-            if let Some(a) = value_node.as_ref().and_then(|n| n.select(&Selector::parse("a").unwrap()).next()) {
-                let name = a.text().collect::<String>().trim().to_string();
-                if !name.is_empty() { studio = Some(name); }
-            }
+        if d.label_is("studio", &label_text) {
+            if let Some(name) = links.first() { studio = Some(name.clone()); }
         }
-        if label_text.contains("rating") {
-            if let Some(v) = Regex::new(r"([0-9]+(?:\.[0-9]+)?)").unwrap().captures(&value_text).and_then(|c| c.get(1)).and_then(|m| m.as_str().parse::<f32>().ok()) {
-                rating = Some(v);
-            }
+        if d.label_is("rating", &label_text) {
+            if let Some(v) = d.capture("number", &value_text) { rating = Some(javdb_rating(v, &value_text)); }
         }
-        if label_text.contains("tags") {
-            let tags = value_node
-                .as_ref()
-                .map(|n| n.select(&Selector::parse("a").unwrap()).map(|a| a.text().collect::<String>().trim().to_string()).filter(|s| !s.is_empty()).collect::<Vec<_>>())
-                .unwrap_or_default();
-            if !tags.is_empty() { genres = tags; }
+        if d.label_is("genres", &label_text) && !links.is_empty() {
+            genres = links.clone();
         }
-        if label_text.contains("actor") {
-            let names = value_node
-                .as_ref()
-                .map(|n| n.select(&Selector::parse("a").unwrap()).map(|a| a.text().collect::<String>().trim().to_string()).filter(|s| !s.is_empty()).collect::<Vec<_>>())
-                .unwrap_or_default();
-            if !names.is_empty() { actor_names = names; }
+        if d.label_is("actors", &label_text) {
+            if let Some(value) = &value_node { cast = javdb_cast(value); }
         }
     }
 
-    // Init advanced fields before filling (already declared above)
-
     // Additional named links
-    let get_one_text = |selector: &str| -> Option<String> {
-        let s = Selector::parse(selector).ok()?;
-        doc.select(&s)
+    let get_one_text = |key: &str| -> Option<String> {
+        doc.select(d.sel(key))
             .next()
             .map(|n| n.text().collect::<String>().trim().to_string())
             .filter(|t| !t.is_empty())
     };
 
-    if let Some(v) = get_one_text("a[href*='/directors/']") { director = Some(v); }
-    if let Some(v) = get_one_text("a[href*='/studios/']") { studio = Some(v); }
-    if let Some(v) = get_one_text("a[href*='/labels/']") { label = Some(v); }
-    if let Some(v) = get_one_text("a[href*='/series/']") { series = Some(v); }
+    if let Some(v) = get_one_text("director") { director = Some(v); }
+    if let Some(v) = get_one_text("studio") { studio = Some(v); }
+    if let Some(v) = get_one_text("label") { label = Some(v); }
+    if let Some(v) = get_one_text("series") { series = Some(v); }
 
-    let plot_sel = Selector::parse(".panel-block .value pre, .panel-block .value p").unwrap();
     let mut plot = doc
-        .select(&plot_sel)
+        .select(d.sel("plot"))
         .map(|n| n.text().collect::<String>().trim().to_string())
         .find(|s| s.len() > 10);
 
-    // Parse key/value meta rows (JavDB often uses dl/dt/dd or blocks). We'll look for dt labels.
-
     // Fallback: scan labeled anchors
-    let label_link_sel = Selector::parse(".panel-block a.tag, .panel-block a[href*='/tags/']").unwrap();
-    for a in doc.select(&label_link_sel) {
+    for a in doc.select(d.sel("tag")) {
         let t = a.text().collect::<String>().trim().to_string();
         if !t.is_empty() {
             genres.push(t);
@@ -400,304 +679,277 @@ async fn parse_javdb_detail(c: &reqwest::Client, url: &str) -> Result<AvDetail>
     genres.sort();
     genres.dedup();
 
-    // Heuristics for duration and rating
+    // Heuristics for duration, rating and release date anywhere on the page
     let body_text = doc.root_element().text().collect::<String>();
-    if let Some(mins) = Regex::new(r"(\d{2,3})\s*min")
-        .unwrap()
-        .captures(&body_text)
-        .and_then(|c| c.get(1))
-        .and_then(|m| m.as_str().parse::<u32>().ok())
-    {
-        duration_minutes = Some(mins);
-    }
-    if duration_minutes.is_none() {
-        if let Some(mins2) = Regex::new(r"(\d{2,3})\s*(分钟|分|min|MIN)")
-            .unwrap()
-            .captures(&body_text)
-            .and_then(|c| c.get(1))
-            .and_then(|m| m.as_str().parse::<u32>().ok())
-        {
-            duration_minutes = Some(mins2);
-        }
-    }
-    if let Some(r) = Regex::new(r"Rating\s*([0-9]+(?:\.[0-9]+)?)")
-        .unwrap()
-        .captures(&body_text)
-        .and_then(|c| c.get(1))
-        .and_then(|m| m.as_str().parse::<f32>().ok())
-    {
-        rating = Some(r);
-    }
-    if rating.is_none() {
-        if let Some(r2) = Regex::new(r"评分\s*([0-9]+(?:\.[0-9]+)?)|Score\s*([0-9]+(?:\.[0-9]+)?)")
-            .unwrap()
-            .captures(&body_text)
-            .and_then(|c| c.get(1).or(c.get(2)))
-            .and_then(|m| m.as_str().parse::<f32>().ok())
-        {
-            rating = Some(r2);
-        }
-    }
-
-    // Release date robust regex
-    if date.is_none() {
-        if let Some(d) = Regex::new(r"(20\d{2}-\d{2}-\d{2})")
-            .unwrap()
-            .captures(&body_text)
-            .and_then(|c| c.get(1))
-            .map(|m| m.as_str().to_string())
-        {
-            date = Some(d);
-        }
-    }
+    if let Some(mins) = d.capture("page_minutes", &body_text) { duration = Runtime::from_minutes(mins); }
+    if let Some(r) = d.capture("page_rating", &body_text) { rating = Some(javdb_rating(r, &body_text)); }
+    if date.is_none() { date = d.capture("page_date", &body_text); }
 
     // Try to parse some named fields by nearby labels
-    let meta_row_sel = Selector::parse(".panel-block").unwrap();
-    for row in doc.select(&meta_row_sel) {
+    let row_label_sel = d.sel("row_label");
+    let row_value_sel = d.sel("row_value");
+    for row in doc.select(d.sel("meta_row")) {
         let label_text = row
-            .select(&Selector::parse(".header, dt").unwrap())
+            .select(row_label_sel)
             .next()
             .map(|n| n.text().collect::<String>())
             .unwrap_or_default();
         let value_text = row
-            .select(&Selector::parse(".value, dd").unwrap())
+            .select(row_value_sel)
             .next()
             .map(|n| n.text().collect::<String>().trim().to_string())
             .unwrap_or_default();
         let lt = label_text.trim();
-        if lt.contains("导演") || lt.contains("Director") {
-            if !value_text.is_empty() { director = Some(value_text.clone()); }
+        if lt.is_empty() { continue; }
+        if d.label_is("director", lt) && !value_text.is_empty() {
+            director = Some(value_text.clone());
         }
-        if lt.contains("片商") || lt.contains("Studio") {
-            if !value_text.is_empty() { studio = Some(value_text.clone()); }
+        if d.label_is("studio", lt) && !value_text.is_empty() {
+            studio = Some(value_text.clone());
         }
-        if lt.contains("厂牌") || lt.contains("Label") {
-            if !value_text.is_empty() { label = Some(value_text.clone()); }
+        if d.label_is("label", lt) && !value_text.is_empty() {
+            label = Some(value_text.clone());
         }
-        if lt.contains("系列") || lt.contains("Series") {
-            if !value_text.is_empty() { series = Some(value_text.clone()); }
+        if d.label_is("series", lt) && !value_text.is_empty() {
+            series = Some(value_text.clone());
         }
-        if lt.contains("时长") || lt.contains("Length") {
-            if let Some(m) = Regex::new(r"(\d{2,3})").unwrap().captures(&value_text).and_then(|c| c.get(1)).and_then(|m| m.as_str().parse::<u32>().ok()) {
-                duration_minutes = Some(m);
-            }
+        if d.label_is("duration", lt) {
+            if let Some(m) = d.capture("minutes", &value_text) { duration = Runtime::from_minutes(m); }
         }
-        if lt.contains("评分") || lt.contains("Rating") {
-            if let Some(v) = Regex::new(r"([0-9]+(?:\.[0-9]+)?)").unwrap().captures(&value_text).and_then(|c| c.get(1)).and_then(|m| m.as_str().parse::<f32>().ok()) {
-                rating = Some(v);
-            }
+        if d.label_is("rating", lt) {
+            if let Some(v) = d.capture("number", &value_text) { rating = Some(javdb_rating(v, &value_text)); }
         }
     }
 
     // Preview images
-    let preview_sel = Selector::parse(".preview-images img, .samples .column img, .tile.is-child img, .sample-box img").unwrap();
     let mut preview_images = doc
-        .select(&preview_sel)
+        .select(d.sel("preview"))
         .filter_map(|img| img.value().attr("src"))
         .map(|s| s.to_string())
         .collect::<Vec<_>>();
 
-    let magnets = extract_magnets_from_text(&body);
+    let magnets = extract_magnets_from_text(body);
     let magnet_infos = extract_magnet_infos_from_javdb(&doc, &magnets);
 
     // Try JSON-LD for richer metadata
-    let (ld_plot, ld_minutes, ld_actors, ld_images, ld_studio) = extract_ld_json_metadata(&doc);
+    let (ld_plot, ld_duration, ld_actors, ld_images, ld_studio) = extract_ld_json_metadata(&doc);
     if plot.is_none() && ld_plot.is_some() { plot = ld_plot; }
-    if duration_minutes.is_none() { duration_minutes = ld_minutes; }
-    if actor_names.is_empty() && !ld_actors.is_empty() { actor_names = ld_actors; }
+    if duration.is_none() { duration = ld_duration; }
+    if cast.is_empty() { cast = ld_actors.into_iter().map(|n| CastMember::new(n, CastRole::Unknown)).collect(); }
     if preview_images.is_empty() && !ld_images.is_empty() { preview_images = ld_images; }
     if studio.is_none() && ld_studio.is_some() { studio = ld_studio; }
-    Ok(AvDetail {
+    // Votes per star count
+    let mut score_distribution = BTreeMap::new();
+    let row_re = d.pattern("distribution_row");
+    for row in doc.select(d.sel("score_distribution")) {
+        let text = row.text().collect::<String>();
+        let Some(caps) = row_re.captures(&text) else { continue };
+        if let (Ok(stars), Ok(votes)) = (caps[1].parse::<u8>(), caps[2].parse::<u32>()) { score_distribution.insert(stars, votes); }
+    }
+    let reviews = reviews::parse(&doc, "javdb");
+    // JavDB files uncensored titles under their own section
+    let censorship = if doc.select(d.sel("uncensored_section")).next().is_some() {
+        Classification::new(Censorship::Uncensored, 0.95)
+    } else {
+        Classification::default()
+    };
+    AvDetail {
         code,
         title,
-        actor_names,
+        original_title: String::new(),
+        clean_title: String::new(),
+        actor_names: actress_names(&cast),
+        cast,
         release_date: date,
         cover_url,
         plot,
-        duration_minutes,
+        duration,
         director,
         studio,
         label,
         series,
         genres,
+        genre_ids: Vec::new(),
         rating,
+        score_distribution,
+        reviews,
         preview_images,
+        sample_images: Vec::new(),
+        trailers: Vec::new(),
         magnet_infos,
         magnets,
-    })
+        censorship,
+        localized: BTreeMap::new(),
+        translated: None,
+        related: javdb_related(&doc),
+    }
 }
 
 async fn fetch_detail_from_sukebei(code: &str) -> Result<AvDetail> {
+    require(Source::Sukebei)?;
+    let wanted = CodeKey::parse(code).ok_or_else(|| BadRequest::new(i18n::t("err.bad_code")))?;
     let c = client();
-    let url = format!("https://sukebei.nyaa.si/?f=0&c=0_0&q={}", encode(code));
-    let body = c.get(&url).send().await?.error_for_status()?.text().await?;
-    let doc = Html::parse_document(&body);
-    let row_sel = Selector::parse("table.torrent-list tbody tr").unwrap();
-    let title_sel = Selector::parse("td[colspan] a, td:nth-child(2) a").unwrap();
-    let mut first_link: Option<String> = None;
-    let mut first_title: String = String::new();
-    let mut first_row_html: Option<scraper::element_ref::ElementRef> = None;
-    for row in doc.select(&row_sel) {
-        if let Some(a) = row.select(&title_sel).next() {
-            let t = a.text().collect::<String>();
-            if t.to_uppercase().contains(code) {
-                if let Some(href) = a.value().attr("href") {
-                    first_link = Some(href.to_string());
-                    first_title = t;
-                    first_row_html = Some(row);
-                    break;
-                }
-            }
-        }
-    }
-    let page_url = first_link.context("Sukebei 未找到该番号")?;
-    let detail_url = if page_url.starts_with("http") { page_url } else { format!("https://sukebei.nyaa.si{}", page_url) };
-    let mut detail = parse_sukebei_detail(&c, &detail_url, code, &first_title).await?;
+    let d = defs::get("sukebei");
+    let page = mirrors::fetch(&c, Source::Sukebei, |base| d.url("search", &[("base", base), ("query", &encode(code))])).await?;
+    let hit = parse_sukebei_hit(&page.body, &wanted).ok_or_else(|| NotFound::new(i18n::tf("err.not_found", &[&"Sukebei"])))?;
+    let detail_url = if hit.href.starts_with("http") { hit.href } else { d.url("detail", &[("base", &page.base), ("path", &hit.href)]) };
+    let body = mirrors::fetch_url(&c, Source::Sukebei, &detail_url).await?.body;
+    let mut detail = parse_sukebei_detail(&body, code, &hit.title);
 
-    // Try to enrich magnet_infos from the row
-    if let Some(row) = first_row_html {
-        let tds: Vec<_> = row.select(&Selector::parse("td").unwrap()).collect();
-        let magnet = row
-            .select(&Selector::parse("a[href^='magnet:']").unwrap())
-            .next()
-            .and_then(|a| a.value().attr("href"))
-            .map(|s| s.to_string());
-        let size = tds.get(3).map(|n| n.text().collect::<String>().trim().to_string());
-        let date = tds.get(4).map(|n| n.text().collect::<String>().trim().to_string());
-        let seeders = tds
-            .get(5)
-            .and_then(|n| n.text().collect::<String>().trim().parse::<u32>().ok());
-        let leechers = tds
-            .get(6)
-            .and_then(|n| n.text().collect::<String>().trim().parse::<u32>().ok());
-        let downloads = tds
-            .get(7)
-            .and_then(|n| n.text().collect::<String>().trim().parse::<u32>().ok());
-
-        if let Some(mag) = magnet.clone() {
-            let mi = MagnetInfo {
-                url: mag.clone(),
-                name: Some(first_title.clone()),
-                size,
-                date,
-                seeders,
-                leechers,
-                downloads,
-                resolution: None,
-                codec: None,
-                avg_bitrate_mbps: None,
-            };
-            // insert if not exists
-            let exists = detail.magnet_infos.iter().any(|x| x.url == mi.url);
-            if !exists {
-                detail.magnet_infos.push(mi);
-            }
-            // also ensure magnets list contains it
-            if !detail.magnets.iter().any(|m| m == &mag) {
-                detail.magnets.push(mag);
-            }
+    // Enrich magnet_infos from the search row
+    if let Some(mi) = hit.magnet {
+        if !detail.magnets.contains(&mi.url) {
+            detail.magnets.push(mi.url.clone());
+        }
+        if !detail.magnet_infos.iter().any(|x| x.url == mi.url) {
+            detail.magnet_infos.push(mi);
         }
     }
 
     Ok(detail)
 }
 
-async fn parse_sukebei_detail(c: &reqwest::Client, url: &str, code: &str, title_guess: &str) -> Result<AvDetail> {
-    let body = c.get(url).send().await?.error_for_status()?.text().await?;
-    let doc = Html::parse_document(&body);
-    let title_sel = Selector::parse(".torrent-name").unwrap();
+/// First Sukebei search row naming the code, with the magnet and stats of that row.
+struct SukebeiHit {
+    href: String,
+    title: String,
+    magnet: Option<MagnetInfo>,
+}
+
+fn parse_sukebei_hit(body: &str, wanted: &CodeKey) -> Option<SukebeiHit> {
+    let doc = Html::parse_document(body);
+    let d = defs::get("sukebei");
+    let title_sel = d.sel("row_title");
+    let (row, a, title) = doc.select(d.sel("row")).find_map(|row| {
+        let a = row.select(title_sel).next()?;
+        let t = a.text().collect::<String>();
+        (wanted.found_in(&t) && a.value().attr("href").is_some()).then_some((row, a, t))
+    })?;
+    let href = a.value().attr("href")?.to_string();
+    let tds: Vec<_> = row.select(d.sel("cell")).collect();
+    let cell = |key: &str| d.column(key).and_then(|i| tds.get(i)).map(|n| n.text().collect::<String>().trim().to_string());
+    let magnet = row.select(d.sel("magnet")).next().and_then(|a| a.value().attr("href")).map(|url| MagnetInfo {
+        url: url.to_string(),
+        name: Some(title.clone()),
+        size: cell("size"),
+        date: cell("date"),
+        seeders: cell("seeders").and_then(|t| t.parse::<u32>().ok()),
+        leechers: cell("leechers").and_then(|t| t.parse::<u32>().ok()),
+        downloads: cell("downloads").and_then(|t| t.parse::<u32>().ok()),
+        resolution: None,
+        codec: None,
+        avg_bitrate_mbps: None,
+    });
+    Some(SukebeiHit { href, title, magnet })
+}
+
+fn parse_sukebei_detail(body: &str, code: &str, title_guess: &str) -> AvDetail {
+    let doc = Html::parse_document(body);
+    let d = defs::get("sukebei");
     let title_text = doc
-        .select(&title_sel)
+        .select(d.sel("torrent_name"))
         .next()
         .map(|n| n.text().collect::<String>())
         .filter(|s| !s.trim().is_empty())
         .unwrap_or_else(|| title_guess.to_string());
 
-    let magnet_sel = Selector::parse("a[href^='magnet:']").unwrap();
     let magnets = doc
-        .select(&magnet_sel)
+        .select(d.sel("magnet"))
         .filter_map(|n| n.value().attr("href"))
         .map(|s| s.to_string())
         .collect::<Vec<_>>();
     let magnet_infos = extract_magnet_infos_from_sukebei(&doc, &magnets);
 
-    Ok(AvDetail {
+    AvDetail {
         code: code.to_uppercase(),
         title: title_text,
+        original_title: String::new(),
+        clean_title: String::new(),
         actor_names: vec![],
+        cast: Vec::new(),
         release_date: None,
         cover_url: None,
         plot: None,
-        duration_minutes: None,
+        duration: None,
         director: None,
         studio: None,
         label: None,
         series: None,
         genres: Vec::new(),
+        genre_ids: Vec::new(),
         rating: None,
+        score_distribution: BTreeMap::new(),
+        reviews: Vec::new(),
         preview_images: Vec::new(),
+        sample_images: Vec::new(),
+        trailers: Vec::new(),
         magnet_infos,
         magnets,
-    })
+        censorship: Classification::default(),
+        localized: BTreeMap::new(),
+        translated: None,
+        related: Vec::new(),
+    }
 }
 
 async fn search_javdb(query: &str) -> Result<Vec<AvItem>> {
+    require(Source::JavDb)?;
     let c = client();
-    let url = format!("{}/search?q={}&f=all", javdb_base(), encode(query));
-    let body = c.get(&url).send().await?.error_for_status()?.text().await?;
-    let doc = Html::parse_document(&body);
-    let card_sel = Selector::parse(".movie-list .item a.box.cover, .movie-list a[href^='/v/'], a.box[href^='/v/']").unwrap();
-    let title_sel = Selector::parse(".video-title").unwrap();
-    let mut items = Vec::new();
-    for a in doc.select(&card_sel) {
-        let href = a.value().attr("href").unwrap_or("");
-        let title = a.select(&title_sel).next().map(|n| n.text().collect::<String>()).unwrap_or_else(|| a.text().collect::<String>());
-        let code = extract_code_from_title(&title).unwrap_or_else(|| href.split('/').last().unwrap_or("").to_string());
-        if !code.is_empty() && !title.is_empty() {
-            items.push(AvItem { code: code.to_uppercase(), title });
-        }
-    }
-    Ok(items)
+    let d = defs::get("javdb");
+    let body = mirrors::fetch(&c, Source::JavDb, |base| d.url("search", &[("base", base), ("query", &encode(query))])).await?.body;
+    Ok(parse_javdb_cards(&body))
 }
 
 async fn search_sukebei(query: &str) -> Result<Vec<AvItem>> {
+    require(Source::Sukebei)?;
     let c = client();
-    let url = format!("https://sukebei.nyaa.si/?f=0&c=0_0&q={}", encode(query));
-    let body = c.get(&url).send().await?.error_for_status()?.text().await?;
-    let doc = Html::parse_document(&body);
-    let row_sel = Selector::parse("table.torrent-list tbody tr").unwrap();
-    let title_sel = Selector::parse("td[colspan] a, td:nth-child(2) a").unwrap();
+    let d = defs::get("sukebei");
+    let body = mirrors::fetch(&c, Source::Sukebei, |base| d.url("search", &[("base", base), ("query", &encode(query))])).await?.body;
+    Ok(parse_sukebei_rows(&body))
+}
+
+/// Torrents of a Sukebei search page whose name carries a code.
+fn parse_sukebei_rows(body: &str) -> Vec<AvItem> {
+    let doc = Html::parse_document(body);
+    let d = defs::get("sukebei");
+    let title_sel = d.sel("row_title");
     let mut items = Vec::new();
-    for row in doc.select(&row_sel) {
-        if let Some(a) = row.select(&title_sel).next() {
+    for row in doc.select(d.sel("row")) {
+        if let Some(a) = row.select(title_sel).next() {
             let title = a.text().collect::<String>();
             if let Some(code) = extract_code_from_title(&title) {
-                items.push(AvItem { code: code.to_uppercase(), title });
+                items.push(AvItem::new(code.to_uppercase(), title));
             }
         }
     }
-    Ok(items)
+    items
 }
 
 async fn list_actor_javdb(actor: &str) -> Result<Vec<AvItem>> {
+    require(Source::JavDb)?;
     let c = client();
-    let url = format!("{}/search?q={}&f=actor", javdb_base(), encode(actor));
-    let body = c.get(&url).send().await?.error_for_status()?.text().await?;
-    let doc = Html::parse_document(&body);
-    let card_sel = Selector::parse(".movie-list .item a.box.cover").unwrap();
-    let title_sel = Selector::parse(".video-title").unwrap();
+    let d = defs::get("javdb");
+    let body = mirrors::fetch(&c, Source::JavDb, |base| d.url("actor_search", &[("base", base), ("query", &encode(actor))])).await?.body;
+    Ok(parse_javdb_actor_cards(&body))
+}
+
+/// Title cards of a JavDB actor search whose title carries a code.
+fn parse_javdb_actor_cards(body: &str) -> Vec<AvItem> {
+    let doc = Html::parse_document(body);
+    let d = defs::get("javdb");
+    let title_sel = d.sel("card_title");
     let mut items = Vec::new();
-    for a in doc.select(&card_sel) {
+    for a in doc.select(d.sel("card")) {
         let title = a
-            .select(&title_sel)
+            .select(title_sel)
             .next()
             .map(|n| n.text().collect::<String>())
             .unwrap_or_default();
         if let Some(code) = extract_code_from_title(&title) {
-            items.push(AvItem { code: code.to_uppercase(), title });
+            items.push(AvItem::new(code.to_uppercase(), title));
         }
     }
-    Ok(items)
+    items
 }
 
 async fn list_actor_sukebei(actor: &str) -> Result<Vec<AvItem>> {
@@ -705,73 +957,34 @@ async fn list_actor_sukebei(actor: &str) -> Result<Vec<AvItem>> {
 }
 
 pub async fn actors(page: usize, per_page: usize, uncensored_only: bool) -> Result<(Vec<ActorItem>, usize)> {
+    // DMM only lists censored releases, so it never backs the uncensored grid
+    if enabled(Source::Dmm) && !uncensored_only {
+        if let Ok(Some((actors, total))) = dmm::actors(page, per_page).await {
+            if !actors.is_empty() { return Ok((actors, total)); }
+        }
+    }
+    if !enabled(Source::JavDb) { return Ok((Vec::new(), 0)); }
     // Prefer uncensored actors grid when requested
     let c = client();
-    let endpoints = if uncensored_only {
-        vec![format!("{}/actors/uncensored?page={}", javdb_base(), page)]
-    } else {
-        vec![
-            format!("{}/actors?o=tr&page={}", javdb_base(), page),
-            format!("{}/rankings/actors?period=w&page={}", javdb_base(), page),
-            format!("{}/rankings/actors?period=m&page={}", javdb_base(), page),
-        ]
-    };
+    let d = defs::get("javdb");
+    let key = if uncensored_only { "actors_uncensored" } else { "actors" };
+    let page_no = page.to_string();
     let mut all: Vec<ActorItem> = Vec::new();
     let mut total_pages: Option<usize> = None;
 
-    for url in &endpoints {
-        util::debug(format!("JavDB actors page: {}", url));
-        let resp = c.get(url).send().await?;
-        if !resp.status().is_success() { continue; }
-        let body = resp.text().await?;
-        let doc = Html::parse_document(&body);
-
-        // Estimate total pages
-        if total_pages.is_none() {
-            let pages = doc
-                .select(&Selector::parse(".pagination-list a.pagination-link").unwrap())
-                .filter_map(|n| n.text().collect::<String>().trim().parse::<usize>().ok())
-                .max();
-            if let Some(p) = pages { total_pages = Some(p); }
-        }
-
-        // Prefer the actors grid structure: #actors .actor-box a strong
-        let grid_sel = Selector::parse("#actors .actor-box a, .actors .actor-box a").unwrap();
-        let strong_sel = Selector::parse("strong").unwrap();
-        let mut grid: Vec<ActorItem> = Vec::new();
-        for (idx, a) in doc.select(&grid_sel).enumerate() {
-            let name_strong = a.select(&strong_sel).next().map(|n| n.text().collect::<String>().trim().to_string());
-            let title_attr = a.value().attr("title").map(|s| s.to_string());
-            // Some title has multiple names separated by comma; pick first
-            let name_from_title = title_attr.clone().and_then(|t| t.split(',').next().map(|s| s.trim().to_string()));
-            let name = name_strong.filter(|s| !s.is_empty()).or(name_from_title).unwrap_or_default();
-            if name.is_empty() { continue; }
-            // If no explicit hot metric, use order (descending)
-            let hot_rank = (per_page as i64 - idx as i64).max(1) as u32;
-            grid.push(ActorItem { name, hot: hot_rank });
-        }
-        if !grid.is_empty() {
-            // apply per_page limit locally
-            let mut limited = grid;
-            if limited.len() > per_page { limited.truncate(per_page); }
-            all = limited;
-            break;
-        }
-
-        // Fallback: anchors-based heuristic (older layout)
-        let a_sel = Selector::parse("a[href^='/actors/']").unwrap();
-        let mut seen: HashMap<String, u32> = HashMap::new();
-        for (idx, a) in doc.select(&a_sel).enumerate() {
-            let name = a.text().collect::<String>().trim().to_string();
-            if name.is_empty() { continue; }
-            let hot_rank = (per_page as i64 - idx as i64).max(1) as u32;
-            let entry = seen.entry(name).or_insert(0);
-            if hot_rank > *entry { *entry = hot_rank; }
-        }
-        if !seen.is_empty() {
-            let mut v = seen.into_iter().map(|(name, hot)| ActorItem { name, hot }).collect::<Vec<_>>();
-            v.sort_by(|a, b| b.hot.cmp(&a.hot).then_with(|| a.name.cmp(&b.name)));
-            all = v;
+    for template in d.templates(key) {
+        util::debug(format!("JavDB actors page: {}", template));
+        let body = match mirrors::fetch(&c, Source::JavDb, |base| defs::fill(template, &[("base", base), ("page", &page_no)])).await {
+            Ok(fetched) => fetched.body,
+            Err(e) => {
+                util::debug(format!("JavDB actors page failed: {:#}", e));
+                continue;
+            }
+        };
+        let (pages, found) = parse_javdb_actors(&body, per_page);
+        if total_pages.is_none() { total_pages = pages; }
+        if !found.is_empty() {
+            all = found;
             break;
         }
     }
@@ -779,10 +992,54 @@ pub async fn actors(page: usize, per_page: usize, uncensored_only: bool) -> Resu
     // Fallback: return empty with total estimation if none found
     let total_pages = total_pages.unwrap_or(page);
     // If we have items count for this page, approximate total items
-    let total_items = total_pages * per_page;
+    let total_items = total_pages.saturating_mul(per_page);
     Ok((all, total_items))
 }
 
+/// Page count and actors of a JavDB actors page, ranked by their position when the
+/// page has no popularity figure.
+fn parse_javdb_actors(body: &str, per_page: usize) -> (Option<usize>, Vec<ActorItem>) {
+    let doc = Html::parse_document(body);
+    let d = defs::get("javdb");
+    let total_pages = doc
+        .select(d.sel("pagination"))
+        .filter_map(|n| n.text().collect::<String>().trim().parse::<usize>().ok())
+        .max();
+
+    // Prefer the actors grid structure: #actors .actor-box a strong
+    let strong_sel = d.sel("actor_name");
+    let mut grid: Vec<ActorItem> = Vec::new();
+    for (idx, a) in doc.select(d.sel("actor_box")).enumerate() {
+        let name_strong = a.select(strong_sel).next().map(|n| n.text().collect::<String>().trim().to_string());
+        let title_attr = a.value().attr("title").map(|s| s.to_string());
+        // Some title has multiple names separated by comma; pick first
+        let name_from_title = title_attr.clone().and_then(|t| t.split(',').next().map(|s| s.trim().to_string()));
+        let name = name_strong.filter(|s| !s.is_empty()).or(name_from_title).unwrap_or_default();
+        if name.is_empty() { continue; }
+        // If no explicit hot metric, use order (descending)
+        let hot_rank = u32::try_from(per_page.saturating_sub(idx).max(1)).unwrap_or(u32::MAX);
+        grid.push(ActorItem { name, hot: hot_rank });
+    }
+    if !grid.is_empty() {
+        // apply per_page limit locally
+        grid.truncate(per_page);
+        return (total_pages, grid);
+    }
+
+    // Fallback: anchors-based heuristic (older layout)
+    let mut seen: HashMap<String, u32> = HashMap::new();
+    for (idx, a) in doc.select(d.sel("actor_link")).enumerate() {
+        let name = a.text().collect::<String>().trim().to_string();
+        if name.is_empty() { continue; }
+        let hot_rank = u32::try_from(per_page.saturating_sub(idx).max(1)).unwrap_or(u32::MAX);
+        let entry = seen.entry(name).or_insert(0);
+        if hot_rank > *entry { *entry = hot_rank; }
+    }
+    let mut v = seen.into_iter().map(|(name, hot)| ActorItem { name, hot }).collect::<Vec<_>>();
+    v.sort_by(|a, b| b.hot.cmp(&a.hot).then_with(|| a.name.cmp(&b.name)));
+    (total_pages, v)
+}
+
 fn extract_code_from_title(title: &str) -> Option<String> {
     let re = Regex::new(r"(?i)([a-z]{2,5})[-_ ]?(\d{2,5})").unwrap();
     if let Some(caps) = re.captures(title) {
@@ -797,9 +1054,12 @@ fn extract_magnets_from_text(body: &str) -> Vec<String> {
     re.find_iter(body).map(|m| m.as_str().to_string()).collect()
 }
 
-fn extract_ld_json_metadata(doc: &Html) -> (Option<String>, Option<u32>, Vec<String>, Vec<String>, Option<String>) {
-    let script_sel = Selector::parse("script[type='application/ld+json']").unwrap();
-    for sc in doc.select(&script_sel) {
+/// (plot, duration, actors, images, studio)
+type LdJsonMetadata = (Option<String>, Option<Runtime>, Vec<String>, Vec<String>, Option<String>);
+
+fn extract_ld_json_metadata(doc: &Html) -> LdJsonMetadata {
+    let script_sel = defs::get("javdb").sel("ld_json");
+    for sc in doc.select(script_sel) {
         let text = sc.text().collect::<String>();
         if text.trim().is_empty() { continue; }
         if let Ok(v) = serde_json::from_str::<serde_json::Value>(&text) {
@@ -807,7 +1067,7 @@ fn extract_ld_json_metadata(doc: &Html) -> (Option<String>, Option<u32>, Vec<Str
             let ctx = v.get("@type").and_then(|t| t.as_str()).unwrap_or("");
             if ctx.eq_ignore_ascii_case("VideoObject") || ctx.eq_ignore_ascii_case("Movie") {
                 let plot = v.get("description").and_then(|x| x.as_str()).map(|s| s.trim().to_string());
-                let duration_minutes = v.get("duration").and_then(|x| x.as_str()).and_then(parse_iso8601_duration_minutes);
+                let duration = v.get("duration").and_then(|x| x.as_str()).and_then(Runtime::parse_iso8601);
                 let actors = v.get("actor").and_then(|x| x.as_array()).map(|arr| {
                     arr.iter().filter_map(|a| a.get("name").and_then(|n| n.as_str()).map(|s| s.to_string())).collect::<Vec<_>>()
                 }).unwrap_or_default();
@@ -815,22 +1075,60 @@ fn extract_ld_json_metadata(doc: &Html) -> (Option<String>, Option<u32>, Vec<Str
                     if let Some(s) = img.as_str() { vec![s.to_string()] } else if let Some(arr) = img.as_array() { arr.iter().filter_map(|i| i.as_str().map(|s| s.to_string())).collect() } else { vec![] }
                 }).unwrap_or_default();
                 let studio = v.get("productionCompany").and_then(|x| x.get("name")).and_then(|s| s.as_str()).map(|s| s.to_string());
-                return (plot, duration_minutes, actors, images, studio);
+                return (plot, duration, actors, images, studio);
             }
         }
     }
     (None, None, Vec::new(), Vec::new(), None)
 }
 
-fn parse_iso8601_duration_minutes(s: &str) -> Option<u32> {
-    // PT1H40M or PT100M
-    let re = Regex::new(r"^PT(?:(\d+)H)?(?:(\d+)M)?$").ok()?;
-    let caps = re.captures(s)?;
-    let h = caps.get(1).and_then(|m| m.as_str().parse::<u32>().ok()).unwrap_or(0);
-    let m = caps.get(2).and_then(|m| m.as_str().parse::<u32>().ok()).unwrap_or(0);
-    Some(h * 60 + m)
+/// Performers of the actors panel row: each link, with the ♀/♂ marker after it.
+fn javdb_cast(value: &scraper::ElementRef) -> Vec<CastMember> {
+    let d = defs::get("javdb");
+    let mut cast: Vec<CastMember> = Vec::new();
+    for el in value.select(d.sel("cast_entry")) {
+        let text = el.text().collect::<String>().trim().to_string();
+        let Some(href) = el.value().attr("href") else {
+            let marker = format!("{} {}", el.value().attr("class").unwrap_or_default(), text);
+            let Some(last) = cast.last_mut() else { continue };
+            // `female` contains `male`, so it goes first
+            if d.pattern("female_marker").is_match(&marker) {
+                last.role = CastRole::Actress;
+            } else if d.pattern("male_marker").is_match(&marker) {
+                last.role = CastRole::Actor;
+            }
+            continue;
+        };
+        if text.is_empty() { continue; }
+        cast.push(CastMember::new(text, CastRole::Unknown).with_id("javdb", d.capture("actor_id", href)));
+    }
+    cast
+}
+
+/// The "you may also like" and "also viewed" tabs of a detail page.
+fn javdb_related(doc: &Html) -> Vec<RelatedItem> {
+    let d = defs::get("javdb");
+    let code_sel = d.sel("related_code");
+    let title_sel = d.sel("related_title");
+    let mut related: Vec<RelatedItem> = Vec::new();
+    for (key, relation) in [("related_similar", Relation::Similar), ("related_also_viewed", Relation::AlsoViewed)] {
+        for a in doc.select(d.sel(key)) {
+            let text = |sel| a.select(sel).next().map(|n| n.text().collect::<String>().trim().to_string()).filter(|t| !t.is_empty());
+            let title = text(title_sel).or_else(|| a.value().attr("title").map(str::to_string)).unwrap_or_default();
+            let Some(code) = text(code_sel).map(|c| c.to_uppercase()).or_else(|| extract_code_from_title(&title)) else { continue };
+            if related.iter().any(|r| r.item.code == code) { continue; }
+            related.push(RelatedItem::new(AvItem::new(code, title), relation));
+        }
+    }
+    related
+}
+
+/// JavDB scores out of 5, with the vote count (`由595人評價`) next to it.
+fn javdb_rating(value: f32, text: &str) -> Rating {
+    Rating::new(value, 5.0, defs::get("javdb").capture("votes", text))
 }
-fn extract_magnet_infos_from_javdb(_doc: &Html, magnets: &Vec<String>) -> Vec<MagnetInfo> {
+
+fn extract_magnet_infos_from_javdb(_doc: &Html, magnets: &[String]) -> Vec<MagnetInfo> {
     // JavDB may not expose table data for magnets in HTML, so primarily return URLs
     magnets
         .iter()
@@ -838,30 +1136,30 @@ fn extract_magnet_infos_from_javdb(_doc: &Html, magnets: &Vec<String>) -> Vec<Ma
         .collect()
 }
 
-fn extract_magnet_infos_from_sukebei(doc: &Html, magnets: &Vec<String>) -> Vec<MagnetInfo> {
+fn extract_magnet_infos_from_sukebei(doc: &Html, magnets: &[String]) -> Vec<MagnetInfo> {
     // sukebei detail page has a table with info, but mapping rows to magnets can be complex; best-effort
     let mut infos: Vec<MagnetInfo> = Vec::new();
+    let d = defs::get("sukebei");
     // Try to read title to infer resolution/codec/bitrate hints
     let title = doc
-        .select(&Selector::parse(".torrent-name").unwrap())
+        .select(d.sel("torrent_name"))
         .next()
         .map(|n| n.text().collect::<String>())
         .unwrap_or_default();
-    let res = Regex::new(r"(\d{3,4}p|\d{3,4}x\d{3,4})").ok()
-        .and_then(|re| re.captures(&title)).map(|c| c.get(1).unwrap().as_str().to_string());
-    let codec = Regex::new(r"(H\.264|H\.265|AVC|HEVC|x264|x265)").ok()
-        .and_then(|re| re.captures(&title)).map(|c| c.get(1).unwrap().as_str().to_string());
+    let res = d.capture::<String>("resolution", &title);
+    let codec = d.capture::<String>("codec", &title);
     let mut size_text: Option<String> = None;
     let mut seeders: Option<u32> = None;
     let mut leechers: Option<u32> = None;
     let mut downloads: Option<u32> = None;
     // Table columns often: Category | Name | Link | Size | Date | S | L | C
-    if let Some(row) = doc.select(&Selector::parse("table.torrent-list tbody tr").unwrap()).next() {
-        let tds: Vec<_> = row.select(&Selector::parse("td").unwrap()).collect();
-        size_text = tds.get(3).map(|n| n.text().collect::<String>().trim().to_string());
-        seeders = tds.get(5).and_then(|n| n.text().collect::<String>().trim().parse::<u32>().ok());
-        leechers = tds.get(6).and_then(|n| n.text().collect::<String>().trim().parse::<u32>().ok());
-        downloads = tds.get(7).and_then(|n| n.text().collect::<String>().trim().parse::<u32>().ok());
+    if let Some(row) = doc.select(d.sel("row")).next() {
+        let tds: Vec<_> = row.select(d.sel("cell")).collect();
+        let cell = |key: &str| d.column(key).and_then(|i| tds.get(i)).map(|n| n.text().collect::<String>().trim().to_string());
+        size_text = cell("size");
+        seeders = cell("seeders").and_then(|t| t.parse::<u32>().ok());
+        leechers = cell("leechers").and_then(|t| t.parse::<u32>().ok());
+        downloads = cell("downloads").and_then(|t| t.parse::<u32>().ok());
     }
 
     // Try to infer bitrate from size and rough duration if present on the page
@@ -869,10 +1167,7 @@ fn extract_magnet_infos_from_sukebei(doc: &Html, magnets: &Vec<String>) -> Vec<M
     if let Some(size_s) = size_text.clone() {
         if let Some((bytes, _unit)) = parse_size_to_bytes(&size_s) {
             let body_text = doc.root_element().text().collect::<String>();
-            if let Some(dur_min) = Regex::new(r"(\d{2,3})\s*(min|分钟)").ok()
-                .and_then(|re| re.captures(&body_text))
-                .and_then(|c| c.get(1)).and_then(|m| m.as_str().parse::<u32>().ok())
-            {
+            if let Some(dur_min) = d.capture::<u32>("minutes", &body_text) {
                 let bits = (bytes as f64) * 8.0;
                 let sec = (dur_min as f64) * 60.0;
                 let mbps = bits / sec / 1_000_000.0;
@@ -914,3 +1209,44 @@ fn parse_size_to_bytes(s: &str) -> Option<(u64, String)> {
 }
 
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(code: &str, score: f32) -> Candidate {
+        Candidate { code: code.into(), title: String::new(), source: "javdb".into(), url: String::new(), score }
+    }
+
+    fn candidates() -> Vec<Candidate> {
+        vec![candidate("ABC-123", 1.0), candidate("ABC-123", 1.0), candidate("ABC-12", 0.4)]
+    }
+
+    #[test]
+    fn pick_is_one_based() {
+        let picked = pick_candidate("ABC-123", candidates(), Some(3)).unwrap().unwrap();
+        assert_eq!(picked.code, "ABC-12");
+        assert!(pick_candidate("ABC-123", candidates(), Some(0)).unwrap_err().is::<BadRequest>());
+        assert!(pick_candidate("ABC-123", candidates(), Some(4)).unwrap_err().is::<BadRequest>());
+    }
+
+    #[test]
+    fn exact_match_is_required_and_unique() {
+        let one = vec![candidate("ABC-123", 1.0), candidate("ABC-12", 0.4)];
+        assert_eq!(pick_candidate("ABC-123", one, None).unwrap().unwrap().code, "ABC-123");
+        assert!(pick_candidate("ABC-123", vec![candidate("ABC-12", 0.4)], None).unwrap().is_none());
+        let err = pick_candidate("ABC-123", candidates(), None).unwrap_err();
+        assert_eq!(err.downcast_ref::<AmbiguousMatch>().map(|a| a.candidates.len()), Some(3));
+    }
+
+    #[test]
+    fn direct_detail_names_its_own_url() {
+        let base = "https://javdb.com";
+        let link = r#"<html><head><link rel="canonical" href="/v/AbC12"></head><body></body></html>"#;
+        assert_eq!(parse_javdb_canonical(link, base).as_deref(), Some("https://javdb.com/v/AbC12"));
+        let og = r#"<html><head><meta property="og:url" content="https://javdb.com/v/AbC12"></head></html>"#;
+        assert_eq!(parse_javdb_canonical(og, base).as_deref(), Some("https://javdb.com/v/AbC12"));
+        let search = r#"<html><head><link rel="canonical" href="/search?q=ABC-123"></head></html>"#;
+        assert_eq!(parse_javdb_canonical(search, base), None);
+    }
+}
@@ -0,0 +1,141 @@
+use anyhow::{Context, Result};
+use axum::extract::{Path, Query, State};
+use axum::http::{header, StatusCode};
+use axum::response::{IntoResponse, Response};
+use axum::routing::get;
+use axum::{Json, Router};
+use serde::Deserialize;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use av::{i18n, nfo, util, AmbiguousMatch, BadRequest, Client, NotFound};
+
+/// Details stay cached this long while the server runs.
+pub const CACHE_TTL: Duration = Duration::from_secs(60 * 60);
+
+/// Lookup failure as a JSON body: 409 with the candidates for ambiguous codes, 400 for
+/// input that is not a code or an out-of-range `pick`, 404 when no source has the title,
+/// 502 for upstream failures.
+struct ApiError(anyhow::Error);
+
+impl From<anyhow::Error> for ApiError {
+    fn from(e: anyhow::Error) -> Self {
+        Self(e)
+    }
+}
+
+impl IntoResponse for ApiError {
+    fn into_response(self) -> Response {
+        if let Some(ambiguous) = self.0.downcast_ref::<AmbiguousMatch>() {
+            return (StatusCode::CONFLICT, Json(ambiguous)).into_response();
+        }
+        let status = if self.0.is::<BadRequest>() {
+            StatusCode::BAD_REQUEST
+        } else if self.0.is::<NotFound>() {
+            StatusCode::NOT_FOUND
+        } else {
+            StatusCode::BAD_GATEWAY
+        };
+        let body = serde_json::json!({ "error": format!("{:#}", self.0) });
+        (status, Json(body)).into_response()
+    }
+}
+
+type ApiResult<T> = std::result::Result<T, ApiError>;
+
+#[derive(Deserialize)]
+struct PickParams {
+    pick: Option<usize>,
+}
+
+#[derive(Deserialize)]
+struct SearchParams {
+    q: String,
+    #[serde(default)]
+    uncen: bool,
+}
+
+#[derive(Deserialize)]
+struct ListParams {
+    #[serde(default)]
+    uncen: bool,
+}
+
+#[derive(Deserialize)]
+struct ActorsParams {
+    #[serde(default = "first_page")]
+    page: usize,
+    #[serde(default = "default_per_page")]
+    per_page: usize,
+    #[serde(default)]
+    uncen: bool,
+}
+
+fn first_page() -> usize {
+    1
+}
+
+fn default_per_page() -> usize {
+    50
+}
+
+/// Largest `per_page` the actors endpoint serves.
+const MAX_PER_PAGE: usize = 100;
+
+/// `av serve`: JSON/XML endpoints over the same lookups as the CLI, with `client`
+/// built with a [`CACHE_TTL`] cache.
+pub async fn run(client: Client, bind: SocketAddr) -> Result<()> {
+    let app = Router::new()
+        .route("/detail/{code}", get(detail))
+        .route("/nfo/{code}", get(nfo_xml))
+        .route("/cover/{code}", get(cover))
+        .route("/search", get(search))
+        .route("/list/{actor}", get(list))
+        .route("/actors", get(actors))
+        .with_state(client);
+    let listener = tokio::net::TcpListener::bind(bind)
+        .await
+        .with_context(|| i18n::tf("serve.bind_failed", &[&bind]))?;
+    println!("{}", i18n::tf("serve.listening", &[&bind]));
+    axum::serve(listener, app).await?;
+    Ok(())
+}
+
+async fn detail(State(client): State<Client>, Path(code): Path<String>, Query(p): Query<PickParams>) -> ApiResult<Response> {
+    util::debug(format!("serve: detail {}", code));
+    let detail = client.detail_pick(&code, p.pick).await?;
+    Ok(Json(detail).into_response())
+}
+
+async fn nfo_xml(State(client): State<Client>, Path(code): Path<String>, Query(p): Query<PickParams>) -> ApiResult<Response> {
+    util::debug(format!("serve: nfo {}", code));
+    let detail = client.detail_pick(&code, p.pick).await?;
+    Ok(([(header::CONTENT_TYPE, "application/xml; charset=utf-8")], nfo::to_nfo(&detail)).into_response())
+}
+
+async fn cover(State(client): State<Client>, Path(code): Path<String>) -> ApiResult<Response> {
+    util::debug(format!("serve: cover {}", code));
+    let image = client.cover(&code).await?;
+    Ok(([(header::CONTENT_TYPE, image.content_type)], image.bytes).into_response())
+}
+
+async fn search(State(client): State<Client>, Query(p): Query<SearchParams>) -> ApiResult<Response> {
+    let mut items = client.search(&p.q).await?;
+    if p.uncen {
+        items.retain(|i| i.censorship.is_uncensored());
+    }
+    Ok(Json(items).into_response())
+}
+
+async fn list(State(client): State<Client>, Path(actor): Path<String>, Query(p): Query<ListParams>) -> ApiResult<Response> {
+    let mut items = client.actor_titles(&actor).await?;
+    if p.uncen {
+        items.retain(|i| i.censorship.is_uncensored());
+    }
+    Ok(Json(items).into_response())
+}
+
+async fn actors(State(client): State<Client>, Query(p): Query<ActorsParams>) -> ApiResult<Response> {
+    let page = client.actors(p.page, p.per_page.clamp(1, MAX_PER_PAGE), p.uncen).await?;
+    Ok(Json(page).into_response())
+}
@@ -0,0 +1,239 @@
+//! Declarative page layouts of the HTML sources: URLs, CSS selectors, label
+//! mappings and post-processing patterns. The definitions in `assets/sources`
+//! are bundled; a file of the same name under `<config dir>/sources/`
+//! overrides them key by key, so a site redesign can be fixed without a release.
+
+use regex::Regex;
+use scraper::Selector;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::i18n;
+use crate::util;
+
+/// Schema version this build understands; overrides with another `version` are ignored.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// Names and contents of the bundled definitions.
+pub const BUNDLED: &[(&str, &str)] = &[
+    ("javdb", include_str!("../../assets/sources/javdb.toml")),
+    ("javlibrary", include_str!("../../assets/sources/javlibrary.toml")),
+    ("sukebei", include_str!("../../assets/sources/sukebei.toml")),
+];
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(untagged)]
+enum Urls {
+    One(String),
+    Many(Vec<String>),
+}
+
+impl Urls {
+    fn all(&self) -> &[String] {
+        match self {
+            Urls::One(u) => std::slice::from_ref(u),
+            Urls::Many(v) => v,
+        }
+    }
+}
+
+/// One source's definition file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct SourceDef {
+    pub version: u32,
+    /// Base URLs substituted for `{base}`, in order of preference
+    pub mirrors: Vec<String>,
+    /// Language tag of pages fetched without a `{locale}`
+    pub language: Option<String>,
+    /// Site locale substituted for `{locale}`, by language tag
+    pub locales: BTreeMap<String, String>,
+    urls: BTreeMap<String, Urls>,
+    pub selectors: BTreeMap<String, String>,
+    pub labels: BTreeMap<String, Vec<String>>,
+    pub columns: BTreeMap<String, usize>,
+    pub patterns: BTreeMap<String, String>,
+    #[serde(skip)]
+    compiled: Compiled,
+}
+
+/// Selectors and patterns compiled once when the definition is loaded.
+#[derive(Debug, Clone, Default)]
+struct Compiled {
+    selectors: HashMap<String, Selector>,
+    patterns: HashMap<String, Regex>,
+}
+
+/// Stand-ins for keys a definition lacks: they never match, so the field stays empty.
+fn no_selector() -> &'static Selector {
+    static NONE: OnceLock<Selector> = OnceLock::new();
+    NONE.get_or_init(|| Selector::parse(":not(*)").unwrap())
+}
+
+fn no_pattern() -> &'static Regex {
+    static NONE: OnceLock<Regex> = OnceLock::new();
+    NONE.get_or_init(|| Regex::new(r"[^\s\S]").unwrap())
+}
+
+impl SourceDef {
+    /// Compiled selector `key`; one matching nothing when the definition lacks it.
+    pub fn sel(&self, key: &str) -> &Selector {
+        self.try_sel(key).unwrap_or_else(|| {
+            util::debug(format!("source definition: selector {} missing", key));
+            no_selector()
+        })
+    }
+
+    /// Compiled selector `key`, for keys only some definitions have.
+    pub fn try_sel(&self, key: &str) -> Option<&Selector> {
+        self.compiled.selectors.get(key)
+    }
+
+    /// Compiled pattern `key`; one matching nothing when the definition lacks it.
+    pub fn pattern(&self, key: &str) -> &Regex {
+        self.compiled.patterns.get(key).unwrap_or_else(|| {
+            util::debug(format!("source definition: pattern {} missing", key));
+            no_pattern()
+        })
+    }
+
+    /// First capture of pattern `key` in `text`, parsed.
+    pub fn capture<T: std::str::FromStr>(&self, key: &str, text: &str) -> Option<T> {
+        self.pattern(key).captures(text)?.get(1)?.as_str().parse().ok()
+    }
+
+    /// Cell index of column `key`.
+    pub fn column(&self, key: &str) -> Option<usize> {
+        self.columns.get(key).copied()
+    }
+
+    /// Whether a row label names `field`.
+    pub fn label_is(&self, field: &str, label: &str) -> bool {
+        let label = label.to_lowercase();
+        self.labels.get(field).is_some_and(|names| names.iter().any(|n| label.contains(&n.to_lowercase())))
+    }
+
+    /// Raw URL templates `key`.
+    pub fn templates(&self, key: &str) -> &[String] {
+        self.urls.get(key).map(Urls::all).unwrap_or_default()
+    }
+
+    /// URL templates `key` filled like [`fill`].
+    pub fn urls(&self, key: &str, vars: &[(&str, &str)]) -> Vec<String> {
+        self.templates(key).iter().map(|t| fill(t, vars)).collect()
+    }
+
+    /// First URL template `key`, filled like [`SourceDef::urls`]; empty (and failing to
+    /// fetch) when the definition lacks it.
+    pub fn url(&self, key: &str, vars: &[(&str, &str)]) -> String {
+        self.urls(key, vars).into_iter().next().unwrap_or_else(|| {
+            util::debug(format!("source definition: url {} missing", key));
+            String::new()
+        })
+    }
+
+    /// Compile every selector and pattern, naming the first one that does not compile.
+    fn compile(&mut self) -> Result<(), String> {
+        for (k, css) in &self.selectors {
+            let sel = Selector::parse(css).map_err(|e| format!("selector {}: {}", k, e))?;
+            self.compiled.selectors.insert(k.clone(), sel);
+        }
+        for (k, re) in &self.patterns {
+            let re = Regex::new(re).map_err(|e| format!("pattern {}: {}", k, e))?;
+            self.compiled.patterns.insert(k.clone(), re);
+        }
+        Ok(())
+    }
+
+    /// Override keys on top of `self`, dropping selectors and patterns that do not compile.
+    fn merge(&mut self, over: SourceDef, origin: &str) {
+        for (k, css) in over.selectors {
+            match Selector::parse(&css).map_err(|e| e.to_string()) {
+                Ok(_) => { self.selectors.insert(k, css); }
+                Err(e) => eprintln!("{}", i18n::tf("defs.bad_selector", &[&origin, &k, &e])),
+            }
+        }
+        for (k, re) in over.patterns {
+            match Regex::new(&re) {
+                Ok(_) => { self.patterns.insert(k, re); }
+                Err(e) => eprintln!("{}", i18n::tf("defs.bad_pattern", &[&origin, &k, &e])),
+            }
+        }
+        if !over.mirrors.is_empty() { self.mirrors = over.mirrors; }
+        if over.language.is_some() { self.language = over.language; }
+        self.locales.extend(over.locales);
+        self.urls.extend(over.urls);
+        self.labels.extend(over.labels);
+        self.columns.extend(over.columns);
+    }
+}
+
+/// `template` with its `{name}` placeholders replaced from `vars`.
+pub fn fill(template: &str, vars: &[(&str, &str)]) -> String {
+    vars.iter().fold(template.to_string(), |acc, (k, v)| acc.replace(&format!("{{{}}}", k), v))
+}
+
+/// Where an override of source `name` is looked up.
+pub fn override_path(name: &str) -> Option<PathBuf> {
+    util::config_dir().map(|d| d.join("sources").join(format!("{}.toml", name)))
+}
+
+/// Bundled definition `name` with its override merged in, compiled. Invalid override
+/// entries are dropped with a warning; an invalid bundled entry is a build defect.
+fn load(name: &str, bundled: &str) -> SourceDef {
+    let mut def: SourceDef = toml::from_str(bundled).unwrap_or_else(|e| panic!("bundled source definition {}: {}", name, e));
+    apply_override(name, &mut def);
+    def.compile().unwrap_or_else(|e| panic!("bundled source definition {}: {}", name, e));
+    def
+}
+
+fn apply_override(name: &str, def: &mut SourceDef) {
+    let Some(path) = override_path(name) else { return };
+    let Ok(text) = std::fs::read_to_string(&path) else { return };
+    let origin = path.display().to_string();
+    match toml::from_str::<SourceDef>(&text) {
+        Ok(over) if over.version != SCHEMA_VERSION => {
+            eprintln!("{}", i18n::tf("defs.bad_version", &[&origin, &over.version, &SCHEMA_VERSION]));
+        }
+        Ok(over) => {
+            util::debug(format!("source definition override: {}", origin));
+            def.merge(over, &origin);
+        }
+        Err(e) => eprintln!("{}: {}", origin, e),
+    }
+}
+
+static DEFS: OnceLock<HashMap<&'static str, SourceDef>> = OnceLock::new();
+
+/// Effective definition of source `name` (`javdb`, `javlibrary`, `sukebei`).
+pub fn get(name: &str) -> &'static SourceDef {
+    let defs = DEFS.get_or_init(|| BUNDLED.iter().map(|(n, text)| (*n, load(n, text))).collect());
+    defs.get(name).unwrap_or_else(|| panic!("unknown source definition {}", name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bundled_definitions_compile() {
+        for (name, text) in BUNDLED {
+            let mut def: SourceDef = toml::from_str(text).unwrap();
+            assert_eq!(def.version, SCHEMA_VERSION, "{}", name);
+            def.compile().unwrap_or_else(|e| panic!("{}: {}", name, e));
+            assert_eq!(def.compiled.selectors.len(), def.selectors.len());
+        }
+    }
+
+    #[test]
+    fn missing_keys_match_nothing() {
+        let def = SourceDef::default();
+        let doc = scraper::Html::parse_document("<p>x</p>");
+        assert_eq!(doc.select(def.sel("nope")).count(), 0);
+        assert_eq!(def.capture::<String>("nope", "anything"), None);
+        assert_eq!(def.column("nope"), None);
+        assert_eq!(def.url("nope", &[]), "");
+    }
+}
@@ -1,8 +1,26 @@
-use anyhow::{Context, Result};
+// Response types mirror the DMM Web Service API v3; not every field is consumed by the CLI.
+// They are `#[non_exhaustive]` so fields can follow the API without a breaking release.
+
+use anyhow::{bail, Context, Result};
+use regex::Regex;
 use reqwest::Url;
-use serde_json::Value;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use crate::breaker;
+use crate::classify::{Censorship, Classification};
+use crate::client::{self, Source};
+use crate::code::CodeKey;
+use crate::i18n::{self, Lang};
+use crate::types::{ActorItem, AvDetail, AvItem, CastMember, CastRole, Date, Rating, RelatedItem, Relation, Runtime, Trailer};
+use crate::util;
+
+const API_BASE: &str = "https://api.dmm.com/affiliate/v3";
 
-use crate::types::AvDetail;
+/// Floor ID of `videoa` (digital adult videos), required by MakerSearch/SeriesSearch/GenreSearch.
+pub const VIDEOA_FLOOR_ID: u32 = 43;
 
 fn env_api_id() -> Option<String> {
     std::env::var("DMM_API_ID").ok().filter(|s| !s.is_empty())
@@ -12,130 +30,821 @@ fn env_affiliate_id() -> Option<String> {
     std::env::var("DMM_AFFILIATE_ID").ok().filter(|s| !s.is_empty())
 }
 
-pub fn dmm_enabled() -> bool {
-    env_api_id().is_some() && env_affiliate_id().is_some()
+// ----------------------- Lenient scalar helpers -----------------------
+// The API is inconsistent: the same field may come back as a JSON number or a string
+// ("status": 200 vs "status": "200", "review.average": "4.50").
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum NumOrStr {
+    Num(f64),
+    Str(String),
 }
 
-pub async fn fetch_detail_from_dmm(code: &str) -> Result<Option<AvDetail>> {
-    if !dmm_enabled() {
-        return Ok(None);
-    }
-
-    let api_id = env_api_id().unwrap();
-    let affiliate_id = env_affiliate_id().unwrap();
-
-    // Build ItemList API URL
-    // See DMM Web Service docs; we search by keyword = code
-    let mut url = Url::parse("https://api.dmm.com/affiliate/v3/ItemList").unwrap();
-    url.query_pairs_mut()
-        .append_pair("api_id", &api_id)
-        .append_pair("affiliate_id", &affiliate_id)
-        .append_pair("site", "DMM")
-        .append_pair("service", "digital")
-        .append_pair("floor", "videoa")
-        .append_pair("hits", "1")
-        .append_pair("sort", "-date")
-        .append_pair("keyword", code);
-
-    let resp_text = reqwest::Client::new()
-        .get(url)
-        .send()
-        .await
-        .context("DMM request failed")?
-        .error_for_status()
-        .context("DMM non-success status")?
-        .text()
-        .await
-        .context("DMM read body failed")?;
-
-    let v: Value = serde_json::from_str(&resp_text).context("DMM parse json failed")?;
-    let items = v
-        .get("result")
-        .and_then(|r| r.get("items"))
-        .and_then(|x| x.as_array())
-        .cloned()
-        .unwrap_or_default();
-    if items.is_empty() {
-        return Ok(None);
-    }
-    let it = &items[0];
-
-    // Helper closures for safe extraction
-    let pick_string = |obj: &Value, path: &[&str]| -> Option<String> {
-        let mut cur = obj;
-        for p in path {
-            cur = cur.get(*p)?;
+impl NumOrStr {
+    fn as_f64(&self) -> Option<f64> {
+        match self {
+            NumOrStr::Num(n) => Some(*n),
+            NumOrStr::Str(s) => s.trim().parse().ok(),
         }
-        cur.as_str().map(|s| s.to_string())
-    };
+    }
 
-    let title = pick_string(it, &["title"]).unwrap_or_default();
-    // Image
-    let cover_url = pick_string(it, &["imageURL", "large"]).or_else(|| pick_string(it, &["imageURL", "list"]));
-    // Release date / Duration
-    let release_date = pick_string(it, &["date"]).or_else(|| pick_string(it, &["volume"]));
-    let duration_minutes = pick_string(it, &["review", "duration"]) // some mirrors
-        .or_else(|| pick_string(it, &["duration"]))
-        .and_then(|s| s.parse::<u32>().ok());
-
-    // Actors, Genres, Studio/Label/Series
-    let mut actor_names: Vec<String> = Vec::new();
-    if let Some(acts) = it.get("iteminfo").and_then(|x| x.get("actress")).and_then(|x| x.as_array()) {
-        for a in acts {
-            if let Some(n) = pick_string(a, &["name"]) {
-                actor_names.push(n);
-            }
+    fn into_string(self) -> String {
+        match self {
+            NumOrStr::Num(n) => n.to_string(),
+            NumOrStr::Str(s) => s,
+        }
+    }
+}
+
+fn de_u32<'de, D: Deserializer<'de>>(d: D) -> std::result::Result<u32, D::Error> {
+    Ok(Option::<NumOrStr>::deserialize(d)?.and_then(|v| v.as_f64()).map(|n| n as u32).unwrap_or(0))
+}
+
+fn de_opt_u32<'de, D: Deserializer<'de>>(d: D) -> std::result::Result<Option<u32>, D::Error> {
+    Ok(Option::<NumOrStr>::deserialize(d)?.and_then(|v| v.as_f64()).map(|n| n as u32))
+}
+
+fn de_opt_f32<'de, D: Deserializer<'de>>(d: D) -> std::result::Result<Option<f32>, D::Error> {
+    Ok(Option::<NumOrStr>::deserialize(d)?.and_then(|v| v.as_f64()).map(|n| n as f32))
+}
+
+fn de_string<'de, D: Deserializer<'de>>(d: D) -> std::result::Result<String, D::Error> {
+    Ok(Option::<NumOrStr>::deserialize(d)?.map(NumOrStr::into_string).unwrap_or_default())
+}
+
+// ----------------------- Response types -----------------------
+
+#[derive(Debug, Deserialize)]
+struct Envelope<T> {
+    result: T,
+}
+
+/// Paging header shared by every search endpoint.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[non_exhaustive]
+pub struct Paging {
+    #[serde(default, deserialize_with = "de_u32")]
+    pub status: u32,
+    #[serde(default, deserialize_with = "de_u32")]
+    pub result_count: u32,
+    #[serde(default, deserialize_with = "de_u32")]
+    pub total_count: u32,
+    #[serde(default, deserialize_with = "de_u32")]
+    pub first_position: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[non_exhaustive]
+pub struct ItemListResult {
+    #[serde(flatten)]
+    pub paging: Paging,
+    #[serde(default)]
+    pub items: Vec<Item>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[non_exhaustive]
+pub struct Item {
+    #[serde(default)]
+    pub service_code: String,
+    #[serde(default)]
+    pub floor_code: String,
+    #[serde(default)]
+    pub content_id: String,
+    #[serde(default)]
+    pub product_id: String,
+    #[serde(default)]
+    pub title: String,
+    /// Runtime in minutes for video floors (sometimes `HH:MM:SS`).
+    #[serde(default)]
+    pub volume: Option<String>,
+    #[serde(default)]
+    pub review: Option<Review>,
+    #[serde(default, rename = "URL")]
+    pub url: Option<String>,
+    #[serde(default, rename = "imageURL")]
+    pub image_url: Option<ImageUrl>,
+    #[serde(default, rename = "sampleImageURL")]
+    pub sample_image_url: Option<SampleImageUrl>,
+    #[serde(default, rename = "sampleMovieURL")]
+    pub sample_movie_url: Option<SampleMovieUrl>,
+    /// `YYYY-MM-DD HH:MM:SS`
+    #[serde(default)]
+    pub date: Option<String>,
+    #[serde(default)]
+    pub iteminfo: ItemInfo,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[non_exhaustive]
+pub struct Review {
+    #[serde(default, deserialize_with = "de_opt_u32")]
+    pub count: Option<u32>,
+    #[serde(default, deserialize_with = "de_opt_f32")]
+    pub average: Option<f32>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[non_exhaustive]
+pub struct ImageUrl {
+    pub list: Option<String>,
+    pub small: Option<String>,
+    pub large: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[non_exhaustive]
+pub struct SampleImageUrl {
+    pub sample_s: Option<SampleImages>,
+    pub sample_l: Option<SampleImages>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[non_exhaustive]
+pub struct SampleImages {
+    #[serde(default)]
+    pub image: Vec<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[non_exhaustive]
+pub struct SampleMovieUrl {
+    pub size_476_306: Option<String>,
+    pub size_560_360: Option<String>,
+    pub size_644_414: Option<String>,
+    pub size_720_480: Option<String>,
+    #[serde(default, deserialize_with = "de_opt_u32")]
+    pub pc_flag: Option<u32>,
+    #[serde(default, deserialize_with = "de_opt_u32")]
+    pub sp_flag: Option<u32>,
+}
+
+impl SampleMovieUrl {
+    /// Player pages by size, smallest first.
+    pub fn trailers(&self) -> Vec<Trailer> {
+        let sizes = [
+            (476, 306, &self.size_476_306),
+            (560, 360, &self.size_560_360),
+            (644, 414, &self.size_644_414),
+            (720, 480, &self.size_720_480),
+        ];
+        sizes.into_iter().filter_map(|(w, h, url)| url.as_ref().map(|u| Trailer::new(w, h, u.as_str()))).collect()
+    }
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[non_exhaustive]
+pub struct ItemInfo {
+    #[serde(default)]
+    pub genre: Vec<Named>,
+    #[serde(default)]
+    pub series: Vec<Named>,
+    #[serde(default)]
+    pub maker: Vec<Named>,
+    #[serde(default)]
+    pub actress: Vec<Named>,
+    /// Male performers, on the floors that list them
+    #[serde(default)]
+    pub actor: Vec<Named>,
+    #[serde(default)]
+    pub director: Vec<Named>,
+    #[serde(default)]
+    pub label: Vec<Named>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[non_exhaustive]
+pub struct Named {
+    #[serde(default, deserialize_with = "de_string")]
+    pub id: String,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub ruby: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[non_exhaustive]
+pub struct ActressSearchResult {
+    #[serde(flatten)]
+    pub paging: Paging,
+    #[serde(default)]
+    pub actress: Vec<Actress>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[non_exhaustive]
+pub struct Actress {
+    #[serde(deserialize_with = "de_string")]
+    pub id: String,
+    pub name: String,
+    pub ruby: Option<String>,
+    #[serde(default, deserialize_with = "de_opt_u32")]
+    pub bust: Option<u32>,
+    pub cup: Option<String>,
+    #[serde(default, deserialize_with = "de_opt_u32")]
+    pub waist: Option<u32>,
+    #[serde(default, deserialize_with = "de_opt_u32")]
+    pub hip: Option<u32>,
+    #[serde(default, deserialize_with = "de_opt_u32")]
+    pub height: Option<u32>,
+    pub birthday: Option<String>,
+    pub blood_type: Option<String>,
+    pub hobby: Option<String>,
+    pub prefectures: Option<String>,
+    #[serde(rename = "imageURL")]
+    pub image_url: Option<ImageUrl>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[non_exhaustive]
+pub struct MakerSearchResult {
+    #[serde(flatten)]
+    pub paging: Paging,
+    #[serde(default)]
+    pub maker: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[non_exhaustive]
+pub struct SeriesSearchResult {
+    #[serde(flatten)]
+    pub paging: Paging,
+    #[serde(default)]
+    pub series: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[non_exhaustive]
+pub struct GenreSearchResult {
+    #[serde(flatten)]
+    pub paging: Paging,
+    #[serde(default)]
+    pub genre: Vec<Entry>,
+}
+
+/// Row of MakerSearch/SeriesSearch/GenreSearch (`maker_id`/`series_id`/`genre_id`).
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[non_exhaustive]
+pub struct Entry {
+    #[serde(alias = "maker_id", alias = "series_id", alias = "genre_id", deserialize_with = "de_string")]
+    pub id: String,
+    pub name: String,
+    pub ruby: Option<String>,
+    pub list_url: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[non_exhaustive]
+pub struct FloorListResult {
+    #[serde(default)]
+    pub site: Vec<Site>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[non_exhaustive]
+pub struct Site {
+    pub name: String,
+    pub code: String,
+    #[serde(default)]
+    pub service: Vec<Service>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[non_exhaustive]
+pub struct Service {
+    pub name: String,
+    pub code: String,
+    #[serde(default)]
+    pub floor: Vec<Floor>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[non_exhaustive]
+pub struct Floor {
+    #[serde(deserialize_with = "de_string")]
+    pub id: String,
+    pub name: String,
+    pub code: String,
+}
+
+// ----------------------- Queries -----------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ItemSort {
+    Rank,
+    Price,
+    PriceDesc,
+    Date,
+    Review,
+    Match,
+}
+
+impl ItemSort {
+    fn as_param(self) -> &'static str {
+        match self {
+            ItemSort::Rank => "rank",
+            ItemSort::Price => "price",
+            ItemSort::PriceDesc => "-price",
+            ItemSort::Date => "date",
+            ItemSort::Review => "review",
+            ItemSort::Match => "match",
+        }
+    }
+}
+
+/// Filter by one of the `iteminfo` facets (ItemList `article`/`article_id`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Article {
+    Actress,
+    Author,
+    Genre,
+    Series,
+    Maker,
+}
+
+impl Article {
+    fn as_param(self) -> &'static str {
+        match self {
+            Article::Actress => "actress",
+            Article::Author => "author",
+            Article::Genre => "genre",
+            Article::Series => "series",
+            Article::Maker => "maker",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct ItemQuery {
+    pub site: String,
+    pub service: String,
+    pub floor: String,
+    pub hits: u32,
+    pub offset: u32,
+    pub sort: ItemSort,
+    pub keyword: Option<String>,
+    pub cid: Option<String>,
+    pub article: Option<(Article, String)>,
+    pub gte_date: Option<String>,
+    pub lte_date: Option<String>,
+}
+
+impl Default for ItemQuery {
+    fn default() -> Self {
+        Self {
+            site: "FANZA".to_string(),
+            service: "digital".to_string(),
+            floor: "videoa".to_string(),
+            hits: 20,
+            offset: 1,
+            sort: ItemSort::Rank,
+            keyword: None,
+            cid: None,
+            article: None,
+            gte_date: None,
+            lte_date: None,
         }
     }
-    let mut genres: Vec<String> = Vec::new();
-    if let Some(gs) = it.get("iteminfo").and_then(|x| x.get("genre")).and_then(|x| x.as_array()) {
-        for g in gs {
-            if let Some(n) = pick_string(g, &["name"]) {
-                genres.push(n);
+}
+
+impl ItemQuery {
+    pub fn keyword(keyword: &str) -> Self {
+        Self { keyword: Some(keyword.to_string()), sort: ItemSort::Match, ..Self::default() }
+    }
+
+    /// `page` is 1-based; the API takes a 1-based item offset.
+    pub fn page(mut self, page: usize, per_page: usize) -> Self {
+        self.hits = per_page.clamp(1, 100) as u32;
+        let skipped = u32::try_from(page.max(1) - 1).unwrap_or(u32::MAX);
+        self.offset = skipped.saturating_mul(self.hits).saturating_add(1);
+        self
+    }
+
+    pub fn sort(mut self, sort: ItemSort) -> Self {
+        self.sort = sort;
+        self
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActressSort {
+    Name,
+    NameDesc,
+    Id,
+    IdDesc,
+}
+
+impl ActressSort {
+    fn as_param(self) -> &'static str {
+        match self {
+            ActressSort::Name => "name",
+            ActressSort::NameDesc => "-name",
+            ActressSort::Id => "id",
+            ActressSort::IdDesc => "-id",
+        }
+    }
+}
+
+// ----------------------- Client -----------------------
+
+pub struct DmmClient {
+    api_id: String,
+    affiliate_id: String,
+    http: reqwest::Client,
+}
+
+impl DmmClient {
+    pub fn new(api_id: impl Into<String>, affiliate_id: impl Into<String>) -> Self {
+        Self {
+            api_id: api_id.into(),
+            affiliate_id: affiliate_id.into(),
+            http: client::settings().http_builder().build().expect("client build"),
+        }
+    }
+
+    /// Client from `DMM_API_ID` / `DMM_AFFILIATE_ID`, or `None` when credentials are missing.
+    pub fn from_env() -> Option<Self> {
+        Some(Self::new(env_api_id()?, env_affiliate_id()?))
+    }
+
+    /// Client from the credentials of the [`crate::Client`] driving the current call.
+    fn current() -> Option<Self> {
+        let (api_id, affiliate_id) = client::settings().dmm.clone()?;
+        Some(Self::new(api_id, affiliate_id))
+    }
+
+    fn url(&self, endpoint: &str, params: &[(&str, String)]) -> Url {
+        let mut url = Url::parse(&format!("{}/{}", API_BASE, endpoint)).unwrap();
+        {
+            let mut q = url.query_pairs_mut();
+            q.append_pair("api_id", &self.api_id)
+                .append_pair("affiliate_id", &self.affiliate_id)
+                .append_pair("output", "json");
+            for (k, v) in params {
+                q.append_pair(k, v);
             }
         }
+        url
+    }
+
+    async fn call<T: DeserializeOwned>(&self, endpoint: &str, params: &[(&str, String)]) -> Result<T> {
+        breaker::check(Source::Dmm)?;
+        let result = self.request(endpoint, params).await;
+        breaker::record(Source::Dmm, result.is_ok());
+        result
     }
-    let director = it.get("iteminfo").and_then(|x| x.get("director")).and_then(|x| x.as_array()).and_then(|arr| arr.get(0)).and_then(|d| pick_string(d, &["name"]));
-    let studio = it.get("iteminfo").and_then(|x| x.get("maker")).and_then(|x| x.as_array()).and_then(|arr| arr.get(0)).and_then(|d| pick_string(d, &["name"]));
-    let label = it.get("iteminfo").and_then(|x| x.get("label")).and_then(|x| x.as_array()).and_then(|arr| arr.get(0)).and_then(|d| pick_string(d, &["name"]));
-    let series = it.get("iteminfo").and_then(|x| x.get("series")).and_then(|x| x.as_array()).and_then(|arr| arr.get(0)).and_then(|d| pick_string(d, &["name"]));
 
-    // Rating (average)
-    let rating = pick_string(it, &["review", "average"]).and_then(|s| s.parse::<f32>().ok());
+    async fn request<T: DeserializeOwned>(&self, endpoint: &str, params: &[(&str, String)]) -> Result<T> {
+        let url = self.url(endpoint, params);
+        util::debug(format!("DMM {}: {:?}", endpoint, params));
+        let resp_text = self
+            .http
+            .get(url)
+            .send()
+            .await
+            .context("DMM request failed")?
+            .error_for_status()
+            .context("DMM non-success status")?
+            .text()
+            .await
+            .context("DMM read body failed")?;
+        let env: Envelope<serde_json::Value> = serde_json::from_str(&resp_text).context("DMM parse json failed")?;
+        // Errors come back with HTTP 200 and `result.status` 400 plus a `message`
+        let status = env.result.get("status").and_then(|s| s.as_u64().or_else(|| s.as_str()?.parse().ok()));
+        if let Some(code) = status.filter(|c| *c >= 400) {
+            let msg = env.result.get("message").and_then(|m| m.as_str()).unwrap_or("");
+            bail!("DMM {} error {}: {}", endpoint, code, msg);
+        }
+        serde_json::from_value(env.result).with_context(|| format!("DMM {} unexpected response", endpoint))
+    }
+
+    pub async fn item_list(&self, q: &ItemQuery) -> Result<ItemListResult> {
+        let mut params = vec![
+            ("site", q.site.clone()),
+            ("service", q.service.clone()),
+            ("floor", q.floor.clone()),
+            ("hits", q.hits.to_string()),
+            ("offset", q.offset.to_string()),
+            ("sort", q.sort.as_param().to_string()),
+        ];
+        if let Some(k) = &q.keyword { params.push(("keyword", k.clone())); }
+        if let Some(cid) = &q.cid { params.push(("cid", cid.clone())); }
+        if let Some((article, id)) = &q.article {
+            params.push(("article", article.as_param().to_string()));
+            params.push(("article_id", id.clone()));
+        }
+        if let Some(d) = &q.gte_date { params.push(("gte_date", d.clone())); }
+        if let Some(d) = &q.lte_date { params.push(("lte_date", d.clone())); }
+        self.call("ItemList", &params).await
+    }
 
-    // Preview images (sample)
-    let mut preview_images: Vec<String> = Vec::new();
-    if let Some(samples) = it.get("sampleImageURL").and_then(|x| x.get("sample_s")).and_then(|x| x.get("image")).and_then(|x| x.as_array()) {
-        for img in samples {
-            if let Some(u) = img.as_str() {
-                preview_images.push(u.to_string());
+    pub async fn actress_search(&self, keyword: Option<&str>, sort: ActressSort, page: usize, per_page: usize) -> Result<ActressSearchResult> {
+        let hits = per_page.clamp(1, 100);
+        let mut params = vec![
+            ("hits", hits.to_string()),
+            ("offset", (page.max(1) - 1).saturating_mul(hits).saturating_add(1).to_string()),
+            ("sort", sort.as_param().to_string()),
+        ];
+        if let Some(k) = keyword { params.push(("keyword", k.to_string())); }
+        self.call("ActressSearch", &params).await
+    }
+
+    pub async fn maker_search(&self, floor_id: u32, initial: Option<&str>, page: usize, per_page: usize) -> Result<MakerSearchResult> {
+        self.call("MakerSearch", &facet_params(floor_id, initial, page, per_page)).await
+    }
+
+    pub async fn series_search(&self, floor_id: u32, initial: Option<&str>, page: usize, per_page: usize) -> Result<SeriesSearchResult> {
+        self.call("SeriesSearch", &facet_params(floor_id, initial, page, per_page)).await
+    }
+
+    pub async fn genre_search(&self, floor_id: u32, initial: Option<&str>, page: usize, per_page: usize) -> Result<GenreSearchResult> {
+        self.call("GenreSearch", &facet_params(floor_id, initial, page, per_page)).await
+    }
+
+    pub async fn floor_list(&self) -> Result<FloorListResult> {
+        self.call("FloorList", &[]).await
+    }
+
+    /// Look up a single title, matching the content ID exactly instead of trusting the first hit.
+    pub async fn find_by_code(&self, code: &str) -> Result<Option<Item>> {
+        let Some(wanted) = CodeKey::parse(code) else { return Ok(None) };
+        // Direct content ID lookup first (`SSIS-123` => `ssis00123`), then keyword search
+        let cid_query = ItemQuery { cid: Some(code_to_content_id(&wanted)), hits: 5, ..ItemQuery::default() };
+        let by_cid = self.item_list(&cid_query).await?;
+        if let Some(it) = best_match(by_cid.items, &wanted) {
+            return Ok(Some(it));
+        }
+        let by_keyword = self.item_list(&ItemQuery { hits: 30, ..ItemQuery::keyword(code) }).await?;
+        Ok(best_match(by_keyword.items, &wanted))
+    }
+}
+
+fn facet_params(floor_id: u32, initial: Option<&str>, page: usize, per_page: usize) -> Vec<(&'static str, String)> {
+    let hits = per_page.clamp(1, 100);
+    let mut params = vec![
+        ("floor_id", floor_id.to_string()),
+        ("hits", hits.to_string()),
+        ("offset", (page.max(1) - 1).saturating_mul(hits).saturating_add(1).to_string()),
+    ];
+    if let Some(i) = initial { params.push(("initial", i.to_string())); }
+    params
+}
+
+// ----------------------- Content ID matching -----------------------
+
+fn code_to_content_id(key: &CodeKey) -> String {
+    format!("{}{:05}{}", key.prefix.to_lowercase(), key.number, key.suffix.to_lowercase())
+}
+
+/// The item spelling `wanted` exactly, else one naming it behind a numeric label prefix.
+fn best_match(items: Vec<Item>, wanted: &CodeKey) -> Option<Item> {
+    let exact = items.iter().position(|it| wanted.matches(&it.content_id) || wanted.matches(&it.product_id));
+    let at = exact.or_else(|| items.iter().position(|it| wanted.matches_content_id(&it.content_id) || wanted.matches_content_id(&it.product_id)))?;
+    items.into_iter().nth(at)
+}
+
+/// Display code for a DMM content ID (`ssis00123` => `SSIS-123`).
+pub fn content_id_to_code(cid: &str) -> String {
+    match CodeKey::from_content_id(cid) {
+        Some(k) if !k.prefix.is_empty() => format!("{}-{:03}{}", k.prefix, k.number, k.suffix),
+        _ => cid.to_uppercase(),
+    }
+}
+
+// ----------------------- Mapping -----------------------
+
+/// DMM only sells mosaicked titles.
+const SOLD_CENSORED: Classification = Classification { category: Censorship::Censored, confidence: 0.95 };
+
+impl Item {
+    pub fn to_av_item(&self) -> AvItem {
+        AvItem { censorship: SOLD_CENSORED, ..AvItem::new(content_id_to_code(&self.content_id), self.title.clone()) }
+    }
+
+    pub fn to_av_detail(&self, code: &str) -> AvDetail {
+        let first_name = |v: &[Named]| v.first().map(|n| n.name.clone());
+        let duration = self.volume.as_deref().and_then(parse_volume);
+        let samples = |pick: fn(&SampleImageUrl) -> Option<&SampleImages>| {
+            self.sample_image_url.as_ref().and_then(pick).map(|s| s.image.clone()).unwrap_or_default()
+        };
+        let preview_images = samples(|s| s.sample_s.as_ref());
+        let sample_images = samples(|s| s.sample_l.as_ref());
+        let trailers = self.sample_movie_url.as_ref().map(SampleMovieUrl::trailers).unwrap_or_default();
+        let member = |n: &Named, role| CastMember::new(n.name.clone(), role).with_id("dmm", Some(n.id.clone()));
+        let actresses = self.iteminfo.actress.iter().map(|n| member(n, CastRole::Actress));
+        let cast = actresses.chain(self.iteminfo.actor.iter().map(|n| member(n, CastRole::Actor))).collect();
+        let mut detail = AvDetail {
+            code: code.to_uppercase(),
+            title: self.title.clone(),
+            original_title: String::new(),
+            clean_title: String::new(),
+            actor_names: self.iteminfo.actress.iter().map(|a| a.name.clone()).collect(),
+            cast,
+            release_date: self.date.as_deref().and_then(Date::find),
+            cover_url: self.image_url.as_ref().and_then(|i| i.large.clone().or_else(|| i.list.clone())),
+            plot: None,
+            duration,
+            director: first_name(&self.iteminfo.director),
+            studio: first_name(&self.iteminfo.maker),
+            label: first_name(&self.iteminfo.label),
+            series: first_name(&self.iteminfo.series),
+            genres: self.iteminfo.genre.iter().map(|g| g.name.clone()).collect(),
+            genre_ids: Vec::new(),
+            rating: self.review.as_ref().and_then(|r| r.average.map(|avg| Rating::new(avg, 5.0, r.count))),
+            score_distribution: BTreeMap::new(),
+            reviews: Vec::new(),
+            preview_images,
+            sample_images,
+            trailers,
+            magnet_infos: Vec::new(),
+            magnets: Vec::new(),
+            censorship: SOLD_CENSORED,
+            localized: BTreeMap::new(),
+            translated: None,
+            related: Vec::new(),
+        };
+        // DMM only serves Japanese metadata
+        detail.localized.insert(Lang::Ja.tag().to_string(), detail.snapshot());
+        detail
+    }
+}
+
+/// `volume` is plain minutes (`120`) on video floors, occasionally `HH:MM:SS`.
+fn parse_volume(v: &str) -> Option<Runtime> {
+    let parts: Vec<u32> = v.trim().split(':').map(|p| p.parse().ok()).collect::<Option<_>>()?;
+    match parts.as_slice() {
+        [m] => Runtime::from_minutes(*m),
+        [h, m, s] => h.checked_mul(3600)?.checked_add(m.checked_mul(60)?)?.checked_add(*s).map(Runtime::from_secs),
+        _ => None,
+    }
+}
+
+// ----------------------- High-level helpers used by scraper -----------------------
+
+/// Detail of `code`, with other titles of its series as related items.
+pub async fn fetch_detail_from_dmm(code: &str) -> Result<Option<AvDetail>> {
+    let Some(client) = DmmClient::current() else { return Ok(None) };
+    let Some(item) = client.find_by_code(code).await? else { return Ok(None) };
+    let mut detail = item.to_av_detail(code);
+    if let Some(series) = item.iteminfo.series.first() {
+        let q = ItemQuery { article: Some((Article::Series, series.id.clone())), ..ItemQuery::default() }.sort(ItemSort::Date).page(1, 20);
+        match client.item_list(&q).await {
+            Ok(res) => {
+                let wanted = CodeKey::parse(code);
+                let others = res.items.iter().map(Item::to_av_item).filter(|i| !wanted.as_ref().is_some_and(|k| k.matches(&i.code)));
+                detail.related = others.map(|i| RelatedItem::new(i, Relation::SameSeries)).collect();
             }
+            Err(e) => util::debug(format!("DMM series listing failed: {:#}", e)),
+        }
+    }
+    Ok(Some(detail))
+}
+
+pub async fn search(query: &str, page: usize, per_page: usize) -> Result<Vec<AvItem>> {
+    let Some(client) = DmmClient::current() else { return Ok(Vec::new()) };
+    let res = client.item_list(&ItemQuery::keyword(query).page(page, per_page)).await?;
+    Ok(res.items.iter().map(Item::to_av_item).collect())
+}
+
+pub async fn latest(limit: usize) -> Result<Vec<AvItem>> {
+    let Some(client) = DmmClient::current() else { return Ok(Vec::new()) };
+    let res = client.item_list(&ItemQuery::default().sort(ItemSort::Date).page(1, limit)).await?;
+    Ok(res.items.iter().map(Item::to_av_item).collect())
+}
+
+/// Titles of an actress, resolved by exact name through ActressSearch first.
+pub async fn list_actress_titles(name: &str) -> Result<Vec<AvItem>> {
+    let Some(client) = DmmClient::current() else { return Ok(Vec::new()) };
+    let found = client.actress_search(Some(name), ActressSort::Id, 1, 20).await?;
+    let Some(actress) = found.actress.iter().find(|a| a.name == name).or(found.actress.first()) else {
+        return Ok(Vec::new());
+    };
+    let mut items = Vec::new();
+    let mut page = 1;
+    loop {
+        let q = ItemQuery { article: Some((Article::Actress, actress.id.clone())), ..ItemQuery::default() }
+            .sort(ItemSort::Date)
+            .page(page, 100);
+        let res = client.item_list(&q).await?;
+        let got = res.items.len();
+        items.extend(res.items.iter().map(Item::to_av_item));
+        if got == 0 || items.len() >= res.paging.total_count as usize || page >= 10 {
+            break;
         }
+        page += 1;
     }
+    Ok(items)
+}
 
-    // Code: DMM may not echo vendor code. Fall back to the provided code.
-    let code_upper = code.to_uppercase();
-
-    let detail = AvDetail {
-        code: code_upper,
-        title,
-        actor_names,
-        release_date,
-        cover_url,
-        plot: None,
-        duration_minutes,
-        director,
-        studio,
-        label,
-        series,
-        genres,
-        rating,
-        preview_images,
-        magnet_infos: Vec::new(),
-        magnets: Vec::new(),
+/// Actress listing; DMM has no popularity metric here so `hot` stays 0.
+pub async fn actors(page: usize, per_page: usize) -> Result<Option<(Vec<ActorItem>, usize)>> {
+    let Some(client) = DmmClient::current() else { return Ok(None) };
+    let res = client.actress_search(None, ActressSort::IdDesc, page, per_page).await?;
+    let actors = res.actress.iter().map(|a| ActorItem { name: a.name.clone(), hot: 0 }).collect();
+    Ok(Some((actors, res.paging.total_count as usize)))
+}
+
+/// Video file behind a trailer player page (see [`Trailer`]). The page embeds an
+/// HTML5 player whose configuration lists the MP4 files; the best quality wins.
+pub async fn trailer_file(page_url: &str) -> Result<String> {
+    let http = client::settings().http_builder().build()?;
+    let page = fetch_page(&http, page_url).await?;
+    let (iframe, mp4) = trailer_patterns();
+    let player = iframe.captures(&page).map(|c| c[1].to_string());
+    let body = match player {
+        Some(src) => fetch_page(&http, &with_scheme(&src.replace("&amp;", "&"))).await?,
+        None => page,
     };
+    let best = mp4.find_iter(&body).map(|m| m.as_str().replace("\\/", "/")).max_by_key(|u| trailer_quality(u));
+    let url = best.with_context(|| i18n::t("err.trailer_unresolved"))?;
+    util::debug(format!("DMM trailer: {} -> {}", page_url, url));
+    Ok(with_scheme(&url))
+}
 
-    Ok(Some(detail))
+/// Player iframe and MP4 links (possibly JSON-escaped) of trailer pages, compiled.
+fn trailer_patterns() -> &'static (Regex, Regex) {
+    static PATTERNS: OnceLock<(Regex, Regex)> = OnceLock::new();
+    PATTERNS.get_or_init(|| {
+        let iframe = Regex::new(r#"<iframe[^>]+src="([^"]+)""#).unwrap();
+        let mp4 = Regex::new(r#"(?:https?:)?(?:\\?/){2}[^"'\s]+?\.mp4"#).unwrap();
+        (iframe, mp4)
+    })
+}
+
+/// Player pages sit behind DMM's age check.
+async fn fetch_page(http: &reqwest::Client, url: &str) -> Result<String> {
+    let resp = http.get(url).header(reqwest::header::COOKIE, "age_check_done=1").send().await?;
+    Ok(resp.error_for_status()?.text().await?)
+}
+
+fn with_scheme(url: &str) -> String {
+    if url.starts_with("//") { format!("https:{}", url) } else { url.to_string() }
 }
 
+/// Rank of a file by its quality suffix (`…_mhb_w.mp4`), lowest first.
+fn trailer_quality(url: &str) -> usize {
+    const SUFFIXES: [&str; 6] = ["_sm_", "_dm_", "_dmb_", "_mmb_", "_mhb_", "_hhb_"];
+    SUFFIXES.iter().position(|s| url.contains(s)).map_or(0, |i| i + 1)
+}
+
+/// HTTP status of the API's FloorList endpoint. The API answers 200 even for bad
+/// credentials; [`browse`] with [`Facet::Floors`] verifies those.
+pub async fn ping() -> Result<u16> {
+    let client = DmmClient::current().with_context(|| i18n::t("err.dmm_unconfigured"))?;
+    let resp = client.http.get(client.url("FloorList", &[])).send().await.context("DMM request failed")?;
+    Ok(resp.status().as_u16())
+}
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Facet {
+    Makers,
+    Series,
+    Genres,
+    Floors,
+}
+
+/// Browse makers/series/genres of the `videoa` floor, or every floor of every site.
+pub async fn browse(facet: Facet, initial: Option<&str>, page: usize, per_page: usize) -> Result<Vec<Entry>> {
+    let client = DmmClient::current().with_context(|| i18n::t("err.dmm_unconfigured"))?;
+    let entries = match facet {
+        Facet::Makers => client.maker_search(VIDEOA_FLOOR_ID, initial, page, per_page).await?.maker,
+        Facet::Series => client.series_search(VIDEOA_FLOOR_ID, initial, page, per_page).await?.series,
+        Facet::Genres => client.genre_search(VIDEOA_FLOOR_ID, initial, page, per_page).await?.genre,
+        Facet::Floors => client
+            .floor_list()
+            .await?
+            .site
+            .iter()
+            .flat_map(|site| {
+                site.service.iter().flat_map(move |svc| {
+                    svc.floor.iter().map(move |f| Entry {
+                        id: f.id.clone(),
+                        name: format!("{} / {} / {}", site.name, svc.name, f.name),
+                        ruby: Some(format!("{}/{}/{}", site.code, svc.code, f.code)),
+                        list_url: None,
+                    })
+                })
+            })
+            .collect(),
+    };
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn volume_in_minutes_or_clock_time() {
+        assert_eq!(parse_volume("120"), Some(Runtime::from_secs(7200)));
+        assert_eq!(parse_volume("01:59:30"), Some(Runtime::from_secs(7170)));
+        assert_eq!(parse_volume("99999999"), None);
+        assert_eq!(parse_volume("9999999:00:00"), None);
+        assert_eq!(parse_volume("abc"), None);
+    }
+}
@@ -1,132 +1,224 @@
 use anyhow::Result;
-use regex::Regex;
-use reqwest::header::{HeaderMap, HeaderValue, USER_AGENT};
-use scraper::{Html, Selector};
-
-use crate::types::AvDetail;
+use scraper::Html;
+use std::collections::BTreeMap;
+
+use crate::classify::Classification;
+use crate::client::{self, Source};
+use crate::sources::{defs, reviews};
+use crate::code::CodeKey;
+use crate::mirrors;
+use crate::types::{actress_names, AvDetail, CastMember, CastRole, Date, Rating, Review, Runtime};
 use crate::util;
 
-const UA: &str = "Mozilla/5.0 (Macintosh; Intel Mac OS X) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/125 Safari/537.36";
-
 fn client() -> reqwest::Client {
-    let mut headers = HeaderMap::new();
-    headers.insert(USER_AGENT, HeaderValue::from_static(UA));
-    reqwest::Client::builder()
-        .default_headers(headers)
-        .cookie_store(true)
-        .build()
-        .expect("client build")
+    client::settings().http_builder().cookie_store(true).build().expect("client build")
 }
 
+/// Detail of `code` in the first language JavLibrary answers in, with the other
+/// requested languages under [`AvDetail::localized`].
 pub async fn fetch_detail_from_javlibrary(code: &str) -> Result<Option<AvDetail>> {
+    let settings = client::settings();
+    if !settings.has(Source::JavLibrary) { return Ok(None); }
     let c = client();
-    // Try multiple locales for better hit rate
-    let locales = ["en", "cn", "ja"];
-    let mut body = String::new();
-    let mut found = false;
-    for loc in &locales {
-        let url = format!("https://www.javlibrary.com/{}/vl_searchbyid.php?keyword={}", loc, code);
-        util::debug(format!("JavLibrary search: {}", url));
-        let resp = c.get(&url).send().await?;
-        if resp.status().is_success() {
-            body = resp.text().await?;
-            found = true;
-            break;
+    let d = defs::get("javlibrary");
+    let requested: Vec<&str> = settings.languages.iter().map(|l| l.tag()).filter(|t| d.locales.contains_key(*t)).collect();
+    let others = d.locales.keys().map(String::as_str).filter(|t| !requested.contains(t));
+    let mut detail: Option<AvDetail> = None;
+    for tag in requested.iter().copied().chain(others) {
+        // Unrequested languages only help to find the title at all
+        if detail.is_some() && !requested.contains(&tag) { break; }
+        match fetch_in(&c, code, &d.locales[tag]).await {
+            Ok(Some(found)) => {
+                let variant = found.snapshot();
+                detail.get_or_insert(found).localized.insert(tag.to_string(), variant);
+            }
+            // Listed nowhere if not in this section
+            Ok(None) if detail.is_none() => return Ok(None),
+            Ok(None) => {}
+            Err(e) => util::debug(format!("JavLibrary ({}) failed: {:#}", tag, e)),
         }
     }
-    if !found { return Ok(None); }
+    Ok(detail)
+}
+
+/// Rating of `code` and page `page` of its user reviews, in the first requested
+/// language the site has.
+pub async fn fetch_reviews(code: &str, page: usize) -> Result<Option<(Option<Rating>, Vec<Review>)>> {
+    let settings = client::settings();
+    if !settings.has(Source::JavLibrary) { return Ok(None); }
+    let c = client();
+    let d = defs::get("javlibrary");
+    let requested = settings.languages.iter().find_map(|l| d.locales.get(l.tag()));
+    let Some(locale) = requested.or_else(|| d.locales.values().next()) else { return Ok(None) };
+    let review_link = |doc: &Html| {
+        let id = doc.select(d.sel("reviews_link")).find_map(|a| a.value().attr("href").and_then(|h| d.capture::<String>("video_id", h)));
+        (rating_of(doc), id)
+    };
+    let Some((base, (rating, id))) = detail_page(&c, code, locale, review_link).await? else { return Ok(None) };
+    let Some(id) = id else { return Ok(Some((rating, Vec::new()))) };
+    let url = d.url("reviews", &[("base", &base), ("locale", locale), ("id", &id), ("page", &page.to_string())]);
+    let body = mirrors::fetch_url(&c, Source::JavLibrary, &url).await?.body;
+    Ok(Some((rating, reviews::parse(&Html::parse_document(&body), "javlibrary"))))
+}
+
+/// JavLibrary's user rating, out of 10.
+fn rating_of(doc: &Html) -> Option<Rating> {
+    let d = defs::get("javlibrary");
+    let text = doc.select(d.sel("rating")).next()?.text().collect::<String>();
+    d.capture("number", &text).map(|v| Rating::new(v, 10.0, None))
+}
+
+/// Where a search answered: on the detail page itself, or with the matching result's link.
+enum SearchHit {
+    Detail,
+    Link(String),
+}
+
+/// Serving mirror and `extract` of the detail page of `code` in the `locale` section of
+/// the site. `extract` runs once every request is done, so no parsed page is held across
+/// an `.await` and the future stays `Send`.
+async fn detail_page<T>(c: &reqwest::Client, code: &str, locale: &str, extract: impl FnOnce(&Html) -> T) -> Result<Option<(String, T)>> {
+    let d = defs::get("javlibrary");
+    let page = mirrors::fetch(c, Source::JavLibrary, |base| d.url("search", &[("base", base), ("locale", locale), ("query", code)])).await?;
+    let Some(wanted) = CodeKey::parse(code) else { return Ok(None) };
+    let body = match search_hit(&page.body, &wanted) {
+        // An exact ID search usually redirects straight to the detail page
+        Some(SearchHit::Detail) => page.body,
+        Some(SearchHit::Link(href)) => {
+            let detail_url = if href.starts_with("http") {
+                href
+            } else {
+                d.url("detail", &[("base", &page.base), ("locale", locale), ("path", href.trim_start_matches('/'))])
+            };
+            util::debug(format!("JavLibrary detail: {}", detail_url));
+            mirrors::fetch_url(c, Source::JavLibrary, &detail_url).await?.body
+        }
+        None => return Ok(None),
+    };
     let doc = Html::parse_document(&body);
-    let first_link = doc
-        .select(&Selector::parse(".video a[href*='?v=']").unwrap())
-        .next()
+    let code_text = doc.select(d.sel("code")).next().map(|n| n.text().collect::<String>().trim().to_string());
+    if let Some(found) = code_text.filter(|t| !wanted.matches(t)) {
+        util::debug(format!("JavLibrary: {} does not match {}", found, code));
+        return Ok(None);
+    }
+    Ok(Some((page.base, extract(&doc))))
+}
+
+/// How a search page answered for `wanted`: the result whose ID matches, never just
+/// the first one; `None` when nothing matches.
+fn search_hit(body: &str, wanted: &CodeKey) -> Option<SearchHit> {
+    let d = defs::get("javlibrary");
+    let doc = Html::parse_document(body);
+    if doc.select(d.sel("detail_page")).next().is_some() { return Some(SearchHit::Detail); }
+    let id_sel = d.sel("result_id");
+    doc.select(d.sel("result"))
+        .find(|a| {
+            a.select(id_sel)
+                .next()
+                .map(|n| wanted.matches(&n.text().collect::<String>()))
+                .unwrap_or(false)
+        })
         .and_then(|a| a.value().attr("href"))
-        .map(|s| s.to_string());
-    let href = match first_link { Some(h) => h, None => return Ok(None) };
-    let detail_url = if href.starts_with("http") { href } else { format!("https://www.javlibrary.com/en/{}", href.trim_start_matches('/')) };
-    util::debug(format!("JavLibrary detail: {}", detail_url));
+        .map(|s| SearchHit::Link(s.to_string()))
+}
 
-    let body = c.get(&detail_url).send().await?.error_for_status()?.text().await?;
-    let doc = Html::parse_document(&body);
+/// Detail of `code` from the `locale` section of the site.
+async fn fetch_in(c: &reqwest::Client, code: &str, locale: &str) -> Result<Option<AvDetail>> {
+    Ok(detail_page(c, code, locale, |doc| parse_detail(doc, code)).await?.map(|(_, detail)| detail))
+}
 
+fn parse_detail(doc: &Html, code: &str) -> AvDetail {
+    let d = defs::get("javlibrary");
     let title = doc
-        .select(&Selector::parse("#video_title").unwrap())
+        .select(d.sel("title"))
         .next()
         .map(|n| n.text().collect::<String>().trim().to_string())
         .unwrap_or_default();
 
     let code_text = doc
-        .select(&Selector::parse("#video_id .text").unwrap())
+        .select(d.sel("code"))
         .next()
         .map(|n| n.text().collect::<String>().trim().to_string())
         .unwrap_or_else(|| code.to_uppercase());
 
     let date = doc
-        .select(&Selector::parse("#video_date .text").unwrap())
+        .select(d.sel("date"))
         .next()
-        .map(|n| n.text().collect::<String>().trim().to_string());
+        .and_then(|n| Date::find(&n.text().collect::<String>()));
 
     let cover_url = doc
-        .select(&Selector::parse("#video_jacket_img").unwrap())
+        .select(d.sel("cover"))
         .next()
         .and_then(|n| n.value().attr("src"))
         .map(|s| s.to_string());
 
-    let actor_names = doc
-        .select(&Selector::parse("#video_cast .star a").unwrap())
-        .map(|n| n.text().collect::<String>().trim().to_string())
-        .filter(|s| !s.is_empty())
+    // JavLibrary lists actresses only
+    let cast = doc
+        .select(d.sel("actor"))
+        .filter_map(|n| {
+            let name = n.text().collect::<String>().trim().to_string();
+            let id = n.value().attr("href").and_then(|h| d.capture("actor_id", h));
+            (!name.is_empty()).then(|| CastMember::new(name, CastRole::Actress).with_id("javlibrary", id))
+        })
         .collect::<Vec<_>>();
 
     let studio = doc
-        .select(&Selector::parse("#video_maker .text a").unwrap())
+        .select(d.sel("studio"))
         .next()
         .map(|n| n.text().collect::<String>().trim().to_string());
 
     let label = doc
-        .select(&Selector::parse("#video_label .text a").unwrap())
+        .select(d.sel("label"))
         .next()
         .map(|n| n.text().collect::<String>().trim().to_string());
 
     let series = doc
-        .select(&Selector::parse("#video_series .text a").unwrap())
+        .select(d.sel("series"))
         .next()
         .map(|n| n.text().collect::<String>().trim().to_string());
 
-    let mut duration_minutes = None;
-    if let Some(t) = doc
-        .select(&Selector::parse("#video_length .text").unwrap())
+    let duration = doc
+        .select(d.sel("duration"))
         .next()
-        .map(|n| n.text().collect::<String>())
-    {
-        if let Some(cap) = Regex::new(r"(\d{2,3})").unwrap().captures(&t) {
-            duration_minutes = cap.get(1).and_then(|m| m.as_str().parse::<u32>().ok());
-        }
-    }
+        .and_then(|n| d.capture("minutes", &n.text().collect::<String>()))
+        .and_then(Runtime::from_minutes);
 
     // Genres
     let genres = doc
-        .select(&Selector::parse("#video_genres .genre a").unwrap())
+        .select(d.sel("genre"))
         .map(|n| n.text().collect::<String>().trim().to_string())
         .collect::<Vec<_>>();
 
-    Ok(Some(AvDetail {
+    AvDetail {
         code: code_text,
         title,
-        actor_names,
+        original_title: String::new(),
+        clean_title: String::new(),
+        actor_names: actress_names(&cast),
+        cast,
         release_date: date,
         cover_url,
         plot: None,
-        duration_minutes,
+        duration,
         director: None,
         studio,
         label,
         series,
         genres,
-        rating: None,
+        genre_ids: Vec::new(),
+        rating: rating_of(doc),
+        score_distribution: BTreeMap::new(),
+        reviews: Vec::new(),
         preview_images: Vec::new(),
+        sample_images: Vec::new(),
+        trailers: Vec::new(),
         magnet_infos: Vec::new(),
         magnets: Vec::new(),
-    }))
+        censorship: Classification::default(),
+        localized: BTreeMap::new(),
+        translated: None,
+        related: Vec::new(),
+    }
 }
 
 
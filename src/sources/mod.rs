@@ -1,4 +1,6 @@
+pub mod defs;
 pub mod dmm;
 pub mod javlibrary;
+pub(crate) mod reviews;
 
 
@@ -0,0 +1,35 @@
+//! User reviews of the HTML sources. Each definition lays its review list out
+//! under the same selector keys: `review_item` per review with `review_author`,
+//! `review_date` and `review_text` inside, plus `review_star` (one element per
+//! star given) and `review_likes` where the site has them.
+
+use scraper::{ElementRef, Html, Selector};
+
+use crate::sources::defs;
+use crate::types::{Date, Review};
+
+fn text_of(el: ElementRef, sel: &Selector) -> Option<String> {
+    el.select(sel).next().map(|n| n.text().collect::<String>().trim().to_string()).filter(|t| !t.is_empty())
+}
+
+/// Reviews on a page of source `name`; entries without text are skipped.
+pub(crate) fn parse(doc: &Html, name: &str) -> Vec<Review> {
+    let d = defs::get(name);
+    let (author_sel, date_sel, text_sel) = (d.sel("review_author"), d.sel("review_date"), d.sel("review_text"));
+    let star_sel = d.try_sel("review_star");
+    let likes_sel = d.try_sel("review_likes");
+    doc.select(d.sel("review_item"))
+        .filter_map(|item| {
+            let text = text_of(item, text_sel)?;
+            let stars = star_sel.map(|s| item.select(s).count()).filter(|&n| n > 0);
+            Some(Review {
+                source: name.to_string(),
+                author: text_of(item, author_sel),
+                date: text_of(item, date_sel).and_then(|t| Date::find(&t)),
+                score: stars.map(|n| n as f32),
+                text,
+                likes: likes_sel.and_then(|s| text_of(item, s)).and_then(|t| d.capture("count", &t)),
+            })
+        })
+        .collect()
+}
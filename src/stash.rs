@@ -0,0 +1,202 @@
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::io::Read;
+use std::time::Duration;
+
+use av::code::extract_code;
+use av::{i18n, util, AvDetail, AvItem, Client, Source};
+
+/// Scraper definition to drop into Stash's `scrapers` directory, matching URLs of the
+/// bundled mirrors; `av stash yaml` prints it for the configured ones.
+pub const SCRAPER_YAML: &str = include_str!("../assets/stash/av.yml");
+
+/// Stash script scraper entry points; each reads one JSON fragment on stdin.
+#[derive(ValueEnum, Clone, Copy, Debug)]
+pub enum Mode {
+    SceneByName,
+    SceneByQueryFragment,
+    SceneByFragment,
+    SceneByUrl,
+    PerformerByName,
+    PerformerByFragment,
+    // Prints the scraper YAML instead of answering a request
+    Yaml,
+}
+
+/// Scene fragment Stash sends; only the fields used to find the code.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct SceneInput {
+    name: Option<String>,
+    title: Option<String>,
+    code: Option<String>,
+    url: Option<String>,
+    urls: Vec<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default)]
+struct PerformerInput {
+    name: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct Named {
+    name: String,
+}
+
+#[derive(Debug, Default, Serialize)]
+struct Scene {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    title: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    code: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    details: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    date: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    director: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    urls: Vec<String>,
+    /// Cover as a data URI, so Stash does not have to hotlink the source
+    #[serde(skip_serializing_if = "Option::is_none")]
+    image: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    studio: Option<Named>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    tags: Vec<Named>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    performers: Vec<Named>,
+}
+
+/// Detail cache of the client, so the cover lookup reuses the detail fetched for the scene.
+pub const CACHE_TTL: Duration = Duration::from_secs(600);
+
+/// `av stash <mode>`: answer one Stash scraper request. Failures are logged to
+/// stderr (which Stash shows in its log) and answered with `null`.
+pub async fn run(client: Client, mode: Mode) -> Result<()> {
+    if let Mode::Yaml = mode {
+        print!("{}", scraper_yaml(&client));
+        return Ok(());
+    }
+    let mut input = String::new();
+    std::io::stdin().read_to_string(&mut input).with_context(|| i18n::t("stash.read_failed"))?;
+    util::debug(format!("stash {:?}: {}", mode, input.trim()));
+    let output = match answer(&client, mode, &input).await {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("{:#}", e);
+            serde_json::Value::Null
+        }
+    };
+    println!("{}", serde_json::to_string(&output)?);
+    Ok(())
+}
+
+/// [`SCRAPER_YAML`] with `sceneByURL` matching the client's JavDB and JavLibrary mirrors.
+fn scraper_yaml(client: &Client) -> String {
+    let host = |base: &String| base.rsplit("://").next().unwrap_or(base).trim_start_matches("www.").to_string();
+    let mut patterns: Vec<String> = client.mirrors(Source::JavDb).iter().map(|b| format!("{}/v/", host(b))).collect();
+    patterns.push("dmm.co.jp".to_string());
+    patterns.extend(client.mirrors(Source::JavLibrary).iter().map(host));
+    let list: String = patterns.iter().map(|p| format!("      - {}\n", p)).collect();
+    // The url list sits between `url:` and the `script:` of the sceneByURL entry
+    let start = SCRAPER_YAML.find("    url:\n").map(|i| i + "    url:\n".len());
+    let end = start.and_then(|s| SCRAPER_YAML[s..].find("    script:").map(|e| s + e));
+    match (start, end) {
+        (Some(s), Some(e)) => format!("{}{}{}", &SCRAPER_YAML[..s], list, &SCRAPER_YAML[e..]),
+        _ => SCRAPER_YAML.to_string(),
+    }
+}
+
+async fn answer(client: &Client, mode: Mode, input: &str) -> Result<serde_json::Value> {
+    let value = match mode {
+        Mode::SceneByName => {
+            let scene: SceneInput = serde_json::from_str(input)?;
+            let query = scene.name.or(scene.title).unwrap_or_default();
+            let items = client.search(&query).await?;
+            serde_json::to_value(items.iter().map(scene_from_item).collect::<Vec<_>>())?
+        }
+        Mode::SceneByQueryFragment | Mode::SceneByFragment => {
+            let scene: SceneInput = serde_json::from_str(input)?;
+            let detail = match scene_code(&scene) {
+                Some(code) => client.detail(&code).await?,
+                None => {
+                    let url = scene.url.iter().chain(&scene.urls).next().with_context(|| i18n::t("stash.no_code"))?;
+                    client.detail_by_url(url).await?
+                }
+            };
+            serde_json::to_value(scene_from_detail(client, &detail, scene.url.into_iter().chain(scene.urls).collect()).await)?
+        }
+        Mode::SceneByUrl => {
+            let scene: SceneInput = serde_json::from_str(input)?;
+            let url = scene.url.with_context(|| i18n::t("stash.no_code"))?;
+            let detail = client.detail_by_url(&url).await?;
+            serde_json::to_value(scene_from_detail(client, &detail, vec![url]).await)?
+        }
+        Mode::PerformerByName => {
+            let performer: PerformerInput = serde_json::from_str(input)?;
+            let name = performer.name.unwrap_or_default();
+            let found = !name.trim().is_empty() && !client.actor_titles(&name).await?.is_empty();
+            let performers = if found { vec![Named { name }] } else { Vec::new() };
+            serde_json::to_value(performers)?
+        }
+        Mode::PerformerByFragment => {
+            let performer: PerformerInput = serde_json::from_str(input)?;
+            let name = performer.name.with_context(|| i18n::t("stash.no_name"))?;
+            serde_json::to_value(Named { name })?
+        }
+        Mode::Yaml => serde_json::Value::Null,
+    };
+    Ok(value)
+}
+
+/// Code from the fragment's code field, else one found in its title or name.
+fn scene_code(scene: &SceneInput) -> Option<String> {
+    let code = scene.code.as_deref().map(str::trim).filter(|c| !c.is_empty());
+    code.map(str::to_string)
+        .or_else(|| scene.title.as_deref().and_then(extract_code))
+        .or_else(|| scene.name.as_deref().and_then(extract_code))
+}
+
+fn scene_from_item(item: &AvItem) -> Scene {
+    Scene { title: Some(item.title.trim().to_string()), code: Some(item.code.clone()), ..Scene::default() }
+}
+
+async fn scene_from_detail(client: &Client, d: &AvDetail, urls: Vec<String>) -> Scene {
+    let image = match client.cover(&d.code).await {
+        Ok(img) => Some(format!("data:{};base64,{}", img.content_type, util::base64(&img.bytes))),
+        Err(e) => {
+            util::debug(format!("stash: cover unavailable: {:#}", e));
+            d.cover_url.clone()
+        }
+    };
+    Scene {
+        title: Some(d.title.clone()),
+        code: Some(d.code.clone()),
+        details: d.plot.clone(),
+        date: d.release_date.map(|date| date.to_string()),
+        director: d.director.clone(),
+        urls,
+        image,
+        studio: d.studio.clone().map(|name| Named { name }),
+        tags: d.genres.iter().map(|g| Named { name: g.clone() }).collect(),
+        performers: d.actor_names.iter().map(|n| Named { name: n.clone() }).collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn yaml_lists_the_configured_mirrors() {
+        let bundled = Client::builder().build().unwrap();
+        assert_eq!(scraper_yaml(&bundled), SCRAPER_YAML);
+        let mirrored = Client::builder().mirrors(Source::JavDb, ["https://javdb.com", "https://javdb521.com/"]).build().unwrap();
+        let yaml = scraper_yaml(&mirrored);
+        assert!(yaml.contains("      - javdb.com/v/\n      - javdb521.com/v/\n      - dmm.co.jp\n      - javlibrary.com\n    script:"));
+    }
+}
@@ -0,0 +1,144 @@
+use anyhow::{bail, Context, Result};
+use colored::*;
+use std::env;
+use std::fs;
+use std::fs::File;
+use std::io::Write;
+use std::process::Stdio;
+use which::which;
+
+// Unix-specific imports
+#[cfg(unix)]
+use std::os::unix::fs::PermissionsExt;
+
+use av::i18n;
+
+#[allow(dead_code)]
+pub async fn download_via_aria2(magnet: &str) -> Result<()> {
+    if which("aria2c").is_err() {
+        bail!(i18n::t("aria2.missing"));
+    }
+
+    let mut cmd = tokio::process::Command::new("aria2c");
+    cmd.arg("--seed-time=0").arg(magnet).stdin(Stdio::null());
+
+    let status = cmd.status().await.with_context(|| i18n::t("aria2.start_failed"))?;
+    if !status.success() {
+        bail!(i18n::tf("aria2.failed", &[&format!("{:?}", status.code())]));
+    }
+    println!("{} {}", i18n::t("aria2.done").green().bold(), magnet);
+    Ok(())
+}
+
+pub async fn open_system_uri(uri: &str) -> Result<()> {
+    #[cfg(target_os = "macos")]
+    let mut cmd = {
+        let mut c = tokio::process::Command::new("open");
+        c.arg(uri);
+        c
+    };
+
+    #[cfg(target_os = "linux")]
+    let mut cmd = {
+        let mut c = tokio::process::Command::new("xdg-open");
+        c.arg(uri);
+        c
+    };
+
+    #[cfg(target_os = "windows")]
+    let mut cmd = {
+        let mut c = tokio::process::Command::new("cmd");
+        c.arg("/C").arg("start").arg("").arg(uri);
+        c
+    };
+
+    let status = cmd.status().await.with_context(|| i18n::t("open.failed"))?;
+    if !status.success() {
+        bail!(i18n::tf("open.cannot", &[&uri]));
+    }
+    println!("{} {}", i18n::t("open.handed").green().bold(), uri);
+    Ok(())
+}
+
+#[allow(dead_code)]
+pub async fn download_magnet(magnet: &str) -> Result<()> {
+    if which("aria2c").is_ok() {
+        download_via_aria2(magnet).await
+    } else {
+        open_system_uri(magnet).await
+    }
+}
+
+pub async fn open_browser_url(url: &str) -> Result<()> {
+    open_system_uri(url).await
+}
+
+pub async fn self_update() -> Result<()> {
+    println!("{}", i18n::t("update.checking"));
+    
+    // 创建临时目录
+    let tmpdir = tempfile::tempdir().with_context(|| i18n::t("update.tmpdir"))?;
+    let installer_path = tmpdir.path().join("install.sh");
+    
+    // 下载安装脚本
+    let install_script_url = "https://raw.github.com/auv-sh/av/master/install.sh";
+    println!("{}", i18n::tf("update.downloading", &[&install_script_url]));
+    
+    let response = reqwest::get(install_script_url).await.with_context(|| i18n::t("update.download_failed"))?;
+    let script_content = response.text().await.with_context(|| i18n::t("update.read_failed"))?;
+    
+    // 写入安装脚本到临时文件
+    let mut file = File::create(&installer_path).with_context(|| i18n::t("update.create_failed"))?;
+    file.write_all(script_content.as_bytes()).with_context(|| i18n::t("update.write_failed"))?;
+    
+    // 设置执行权限
+    #[cfg(unix)]
+    {
+        let mut perms = fs::metadata(&installer_path).with_context(|| i18n::t("update.perm_read_failed"))?.permissions();
+        perms.set_mode(0o755);
+        fs::set_permissions(&installer_path, perms).with_context(|| i18n::t("update.perm_set_failed"))?;
+    }
+    
+    // Windows doesn't need explicit permission setting for execution
+    
+    // 获取当前可执行文件路径
+    let _current_exe = env::current_exe().with_context(|| i18n::t("update.exe_path"))?;
+    
+    // 执行安装脚本
+    println!("{}", i18n::t("update.running"));
+    
+    #[cfg(unix)]
+    let status = tokio::process::Command::new("sh")
+        .arg(&installer_path)
+        .status()
+        .await
+        .with_context(|| i18n::t("update.run_failed"))?;
+        
+    #[cfg(windows)]
+    let status = {
+        // Windows 需要使用 PowerShell 或 cmd 来执行脚本
+        // 首先将 .sh 脚本内容转换为 .ps1 脚本
+        let ps_path = tmpdir.path().join("install.ps1");
+        let ps_content = script_content.replace("\r\n", "\n").replace("\n", "\r\n");
+        let mut ps_file = File::create(&ps_path).with_context(|| i18n::t("update.ps_create_failed"))?;
+        ps_file.write_all(ps_content.as_bytes()).with_context(|| i18n::t("update.ps_write_failed"))?;
+        
+        // 使用 PowerShell 执行脚本
+        tokio::process::Command::new("powershell")
+            .arg("-ExecutionPolicy")
+            .arg("Bypass")
+            .arg("-File")
+            .arg(&ps_path)
+            .status()
+            .await
+            .with_context(|| i18n::t("update.run_failed"))?
+    };
+    
+    if status.success() {
+        println!("{}", i18n::t("update.success").green().bold());
+    } else {
+        bail!(i18n::tf("update.exit_failed", &[&format!("{:?}", status.code())]));
+    }
+    
+    Ok(())
+}
@@ -0,0 +1,276 @@
+use colored::Colorize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::OnceLock;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+const GAP: &str = "  ";
+/// Narrowest a shrinkable column gets before the table is allowed to overflow.
+const MIN_FLEX_WIDTH: usize = 8;
+
+static WRAP: AtomicBool = AtomicBool::new(false);
+static COLUMNS: OnceLock<Vec<String>> = OnceLock::new();
+
+/// Global table options from the CLI: wrap instead of truncating, and the column selection.
+pub fn set_options(wrap: bool, columns: Option<&str>) {
+    WRAP.store(wrap, Ordering::Relaxed);
+    if let Some(cols) = columns {
+        let keys = cols.split(',').map(|c| c.trim().to_lowercase()).filter(|c| !c.is_empty()).collect();
+        let _ = COLUMNS.set(keys);
+    }
+}
+
+/// Terminal columns, or `None` when stdout is not a terminal (pipes get full-width rows).
+pub fn terminal_width() -> Option<usize> {
+    use std::io::IsTerminal;
+    if let Some(cols) = std::env::var("COLUMNS").ok().and_then(|c| c.parse::<usize>().ok()) {
+        return Some(cols);
+    }
+    if !std::io::stdout().is_terminal() {
+        return None;
+    }
+    ratatui::crossterm::terminal::size().ok().map(|(w, _)| w as usize)
+}
+
+/// Width in terminal cells: CJK and full-width characters count as two.
+pub fn display_width(s: &str) -> usize {
+    UnicodeWidthStr::width(s)
+}
+
+/// Cut `s` to at most `width` cells, marking the cut with `…`.
+pub fn truncate(s: &str, width: usize) -> String {
+    if display_width(s) <= width {
+        return s.to_string();
+    }
+    let mut out = String::new();
+    let mut used = 0;
+    for ch in s.chars() {
+        let w = ch.width().unwrap_or(0);
+        if used + w + 1 > width { break; }
+        out.push(ch);
+        used += w;
+    }
+    out.push('…');
+    out
+}
+
+/// Break `s` into lines of at most `width` cells, preferring to break at spaces.
+pub fn wrap(s: &str, width: usize) -> Vec<String> {
+    let width = width.max(1);
+    let mut lines = Vec::new();
+    for para in s.split('\n') {
+        let mut line = String::new();
+        let mut used = 0;
+        for ch in para.chars() {
+            let w = ch.width().unwrap_or(0);
+            if used + w > width {
+                // Move a trailing partial word down when the line has a space to break at
+                let carry = match line.rfind(' ') {
+                    Some(idx) if !ch.is_whitespace() && idx > 0 => line.split_off(idx + 1),
+                    _ => String::new(),
+                };
+                lines.push(line.trim_end().to_string());
+                used = display_width(&carry);
+                line = carry;
+                if ch == ' ' { continue; }
+            }
+            line.push(ch);
+            used += w;
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+fn pad(s: &str, width: usize, align: Align) -> String {
+    let fill = " ".repeat(width.saturating_sub(display_width(s)));
+    match align {
+        Align::Left => format!("{}{}", s, fill),
+        Align::Right => format!("{}{}", fill, s),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Align {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone)]
+pub struct Column {
+    /// Name used by `--columns`
+    pub key: &'static str,
+    pub header: String,
+    pub align: Align,
+    /// Shrinks (truncates or wraps) when the table is wider than the terminal
+    pub flex: bool,
+}
+
+impl Column {
+    pub fn new(key: &'static str, header: impl Into<String>) -> Self {
+        Self { key, header: header.into(), align: Align::Left, flex: false }
+    }
+
+    pub fn right(mut self) -> Self {
+        self.align = Align::Right;
+        self
+    }
+
+    pub fn flex(mut self) -> Self {
+        self.flex = true;
+        self
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Table {
+    columns: Vec<Column>,
+    rows: Vec<Vec<String>>,
+    header: bool,
+    wrap: bool,
+}
+
+impl Table {
+    pub fn new(columns: Vec<Column>) -> Self {
+        Self { columns, rows: Vec::new(), header: true, wrap: WRAP.load(Ordering::Relaxed) }
+    }
+
+    /// Key/value layout without header or separator, always wrapping (used by detail views).
+    pub fn key_value(label_header: impl Into<String>, value_header: impl Into<String>) -> Self {
+        let mut t = Self::new(vec![Column::new("label", label_header), Column::new("value", value_header).flex()]);
+        t.header = false;
+        t.wrap = true;
+        t
+    }
+
+    pub fn row(&mut self, cells: Vec<String>) {
+        self.rows.push(cells);
+    }
+
+    /// Apply `--columns`: keep the listed columns in the listed order; unknown keys are ignored
+    /// and an empty selection leaves the table as is.
+    fn selected(&self) -> (Vec<&Column>, Vec<usize>) {
+        let all = || (self.columns.iter().collect(), (0..self.columns.len()).collect());
+        let Some(keys) = COLUMNS.get().filter(|_| self.header) else { return all() };
+        let idx: Vec<usize> = keys
+            .iter()
+            .filter_map(|k| self.columns.iter().position(|c| c.key == k))
+            .collect();
+        if idx.is_empty() { return all(); }
+        (idx.iter().map(|&i| &self.columns[i]).collect(), idx)
+    }
+
+    fn widths(&self, cols: &[&Column], idx: &[usize], max_width: Option<usize>) -> Vec<usize> {
+        let mut widths: Vec<usize> = cols
+            .iter()
+            .zip(idx)
+            .map(|(c, &i)| {
+                let cells = self.rows.iter().filter_map(|r| r.get(i)).flat_map(|s| s.split('\n')).map(display_width);
+                let header = if self.header { display_width(&c.header) } else { 0 };
+                cells.chain(std::iter::once(header)).max().unwrap_or(0)
+            })
+            .collect();
+        let Some(max_width) = max_width else { return widths };
+        let total: usize = widths.iter().sum::<usize>() + GAP.len() * widths.len().saturating_sub(1);
+        if total <= max_width { return widths; }
+        let flex: Vec<usize> = (0..cols.len()).filter(|&i| cols[i].flex).collect();
+        if flex.is_empty() { return widths; }
+        let fixed: usize = (0..cols.len()).filter(|i| !cols[*i].flex).map(|i| widths[i]).sum();
+        let gaps = GAP.len() * cols.len().saturating_sub(1);
+        let available = max_width.saturating_sub(fixed + gaps);
+        let share = (available / flex.len()).max(MIN_FLEX_WIDTH);
+        for i in flex {
+            widths[i] = widths[i].min(share);
+        }
+        widths
+    }
+
+    pub fn render(&self) -> String {
+        self.render_with_width(terminal_width())
+    }
+
+    pub fn render_with_width(&self, max_width: Option<usize>) -> String {
+        let (cols, idx) = self.selected();
+        let widths = self.widths(&cols, &idx, max_width);
+        let last = cols.len().saturating_sub(1);
+        let mut out = String::new();
+        let mut push_line = |cells: Vec<String>| {
+            out.push_str(cells.join(GAP).trim_end());
+            out.push('\n');
+        };
+        if self.header {
+            push_line(cols.iter().zip(&widths).enumerate().map(|(n, (c, &w))| {
+                let text = if n == last && c.align == Align::Left { c.header.clone() } else { pad(&c.header, w, c.align) };
+                text.bold().to_string()
+            }).collect());
+            push_line(widths.iter().map(|&w| "-".repeat(w.max(1))).collect());
+        }
+        for row in &self.rows {
+            // Each cell becomes one or more physical lines
+            let cell_lines: Vec<Vec<String>> = idx
+                .iter()
+                .zip(&cols)
+                .zip(&widths)
+                .map(|((&i, c), &w)| {
+                    let text = row.get(i).map(String::as_str).unwrap_or("");
+                    if !c.flex {
+                        text.split('\n').map(str::to_string).collect()
+                    } else if self.wrap {
+                        wrap(text, w)
+                    } else {
+                        text.split('\n').map(|l| truncate(l, w)).collect()
+                    }
+                })
+                .collect();
+            let height = cell_lines.iter().map(Vec::len).max().unwrap_or(1);
+            for line in 0..height {
+                push_line(cell_lines.iter().zip(&cols).zip(&widths).enumerate().map(|(n, ((lines, c), &w))| {
+                    let text = lines.get(line).map(String::as_str).unwrap_or("");
+                    if n == last && c.align == Align::Left { text.to_string() } else { pad(text, w, c.align) }
+                }).collect());
+            }
+        }
+        out
+    }
+
+    pub fn print(&self) {
+        print!("{}", self.render());
+    }
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncate_never_splits_a_double_width_char() {
+        assert_eq!(truncate("日本語タイトル", 6), "日本…");
+        assert_eq!(truncate("ab日本", 4), "ab…");
+        assert_eq!(truncate("ab日本", 5), "ab日…");
+        assert_eq!(truncate("ab日本", 6), "ab日本");
+        assert!(display_width(&truncate("日本語タイトル", 7)) <= 7);
+    }
+
+    #[test]
+    fn wrap_counts_cells() {
+        assert_eq!(wrap("日本語タイトル", 5), vec!["日本", "語タ", "イト", "ル"]);
+        assert_eq!(wrap("hello world", 7), vec!["hello", "world"]);
+        assert_eq!(wrap("ab日", 3), vec!["ab", "日"]);
+    }
+
+    fn squeezed(wrap: bool) -> Vec<String> {
+        colored::control::set_override(false);
+        let mut t = Table::new(vec![Column::new("code", "Code"), Column::new("title", "Title").flex()]);
+        t.wrap = wrap;
+        t.row(vec!["SSIS-001".into(), "日本語のとても長いタイトルです".into()]);
+        t.render_with_width(Some(12)).lines().skip(2).map(str::to_string).collect()
+    }
+
+    #[test]
+    fn flex_column_stops_at_min_width() {
+        assert_eq!(squeezed(false), vec!["SSIS-001  日本語…"]);
+        let lines = squeezed(true);
+        assert_eq!(lines[0], "SSIS-001  日本語の");
+        assert!(lines.iter().all(|l| display_width(&l[10..]) <= MIN_FLEX_WIDTH));
+    }
+}
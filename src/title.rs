@@ -0,0 +1,146 @@
+//! Title normalization. Scraped titles carry the code, actor names, bracketed site
+//! tags and release markers (`[HD]`, `1080p`, `hhd800.com@`), mostly from
+//! Sukebei torrent names and page `<title>` fallbacks; [`clean`] strips them.
+
+use regex::Regex;
+use std::sync::OnceLock;
+
+use crate::code::CodeKey;
+
+/// Half-width katakana and punctuation from U+FF61, in code point order.
+const HALF_KANA: &str = "。「」、・ヲァィゥェォャュョッーアイウエオカキクケコサシスセソタチツテトナニヌネノハヒフヘホマミムメモヤユヨラリルレロワン";
+
+/// Release markers and tags, matched against whole tokens or bracket contents.
+const NOISE: &str = r"(?i)^(?:(?:2160|1080|720|480)p|[FU]?HD|SD|4K|60FPS|HEVC|[xh]\.?26[45]|-?C|-?UC|CH?S|SUB|uncensored|leak(?:ed)?|中文字幕|字幕|无码|無碼|無修正|流出|破解|高清|有码|有碼)$";
+
+/// The fixed patterns of [`clean`], compiled once.
+struct Patterns {
+    noise: Regex,
+    site: Regex,
+    brackets: Regex,
+    parens: Regex,
+    domain: Regex,
+    extension: Regex,
+}
+
+fn patterns() -> &'static Patterns {
+    static PATTERNS: OnceLock<Patterns> = OnceLock::new();
+    PATTERNS.get_or_init(|| Patterns {
+        noise: Regex::new(NOISE).unwrap(),
+        // Page titles end in `| JavDB …` or `- JAVLibrary`
+        site: Regex::new(r"(?i)\s+[|\-]\s+[^|]*(?:javdb|javlibrary|sukebei|nyaa|fanza|dmm)[^|]*$").unwrap(),
+        brackets: Regex::new(r"【[^】]*】|〖[^〗]*〗|\[[^\]]*\]").unwrap(),
+        parens: Regex::new(r"\(([^)]*)\)").unwrap(),
+        domain: Regex::new(r"(?i)[\w-]+\.(?:com|net|org|la|cc|tv|me|xyz|info|vip|club)@?").unwrap(),
+        extension: Regex::new(r"(?i)\.(?:mp4|mkv|avi|wmv|m4v|ts)$").unwrap(),
+    })
+}
+
+/// Full-width ASCII and ideographic spaces to half-width, half-width katakana to full-width.
+pub fn normalize_width(text: &str) -> String {
+    let kana: Vec<char> = HALF_KANA.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        let mapped = match c as u32 {
+            0xFF01..=0xFF5E => char::from_u32(c as u32 - 0xFEE0).unwrap_or(c),
+            0x3000 => ' ',
+            0xFF61..=0xFF9D => kana[(c as u32 - 0xFF61) as usize],
+            // Voiced and semi-voiced marks combine with the kana before them
+            0xFF9E | 0xFF9F => {
+                let semi = c == '\u{FF9F}';
+                match (out.pop(), semi) {
+                    (Some('ウ'), false) => 'ヴ',
+                    (Some(p), false) if "カキクケコサシスセソタチツテトハヒフヘホ".contains(p) => char::from_u32(p as u32 + 1).unwrap_or(p),
+                    (Some(p), true) if "ハヒフヘホ".contains(p) => char::from_u32(p as u32 + 2).unwrap_or(p),
+                    (prev, _) => {
+                        out.extend(prev);
+                        if semi { '゜' } else { '゛' }
+                    }
+                }
+            }
+            _ => c,
+        };
+        out.push(mapped);
+    }
+    out
+}
+
+/// `title` without the code, leading or trailing actor names, bracketed tags, site
+/// names and release markers, width-normalized. The normalized title when nothing is left.
+pub fn clean(title: &str, code: &str, actors: &[String]) -> String {
+    let normalized = normalize_width(title);
+    let p = patterns();
+    let noise = &p.noise;
+    let mut s = p.site.replace(&normalized, "").into_owned();
+    s = p.brackets.replace_all(&s, " ").into_owned();
+    s = p
+        .parens
+        .replace_all(&s, |c: &regex::Captures| {
+            let all_noise = c[1].split([' ', ',', '/']).filter(|t| !t.is_empty()).all(|t| noise.is_match(t));
+            if all_noise { " ".to_string() } else { c[0].to_string() }
+        })
+        .into_owned();
+    s = p.domain.replace_all(&s, " ").into_owned();
+    s = p.extension.replace(&s, "").into_owned();
+    if let Some(key) = CodeKey::parse(code) {
+        if let Ok(re) = Regex::new(&format!(r"(?i)(^|[^A-Z0-9]){}([^A-Z0-9]|$)", key.pattern())) {
+            s = re.replace_all(&s, "${1} ${2}").into_owned();
+        }
+    }
+    let mut tokens: Vec<&str> = s.split_whitespace().filter(|t| !noise.is_match(t)).collect();
+    let actors: Vec<String> = actors.iter().map(|a| normalize_width(a)).filter(|a| !a.is_empty()).collect();
+    // Multi-word names span several tokens
+    let mut stripped = true;
+    while stripped {
+        stripped = false;
+        for a in &actors {
+            let words: Vec<&str> = a.split_whitespace().collect();
+            if tokens.len() > words.len() && tokens.ends_with(&words) {
+                tokens.truncate(tokens.len() - words.len());
+                stripped = true;
+            }
+            if tokens.len() > words.len() && tokens.starts_with(&words) {
+                tokens.drain(..words.len());
+                stripped = true;
+            }
+        }
+    }
+    let separators: &[char] = &['-', '_', '|', '/', ':', ',', '.', '・', '、', '。', '~'];
+    let joined = tokens.join(" ");
+    let out = joined.trim_matches(|c: char| c.is_whitespace() || separators.contains(&c));
+    if out.is_empty() { normalized.trim().to_string() } else { out.to_string() }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn width_folding() {
+        assert_eq!(normalize_width("ｶﾞｷﾞﾊﾟｳﾞ"), "ガギパヴ");
+        assert_eq!(normalize_width("ｱﾞ"), "ア゛");
+        assert_eq!(normalize_width("ﾞ"), "゛");
+        assert_eq!(normalize_width("ﾎﾟﾝ"), "ポン");
+        assert_eq!(normalize_width("ＡＢＣ－１２３　タイトル"), "ABC-123 タイトル");
+    }
+
+    #[test]
+    fn clean_strips_tags_and_markers() {
+        let clean = |t: &str| clean(t, "SSIS-001", &[]);
+        assert_eq!(clean("[HD] SSIS-001 美少女の日常 【中文字幕】"), "美少女の日常");
+        assert_eq!(clean("hhd800.com@SSIS-001 美少女の日常"), "美少女の日常");
+        assert_eq!(clean("ssis001 美少女の日常 (1080p, HEVC).mp4"), "美少女の日常");
+        assert_eq!(clean("SSIS-001 美少女の日常 (Part 2)"), "美少女の日常 (Part 2)");
+        assert_eq!(clean("SSIS-001 美少女の日常 | JavDB 成人影片數據庫"), "美少女の日常");
+        assert_eq!(clean("SSIS-001 ｶﾞｰﾙ"), "ガール");
+        assert_eq!(clean("SSIS-001"), "SSIS-001");
+    }
+
+    #[test]
+    fn clean_strips_actor_names_at_the_ends() {
+        let actors = ["Yua Mikami".to_string(), "三上悠亜".to_string()];
+        assert_eq!(clean("SSIS-001 Morning Walk Yua Mikami", "SSIS-001", &actors), "Morning Walk");
+        assert_eq!(clean("三上悠亜 SSIS-001 美少女の日常", "SSIS-001", &actors), "美少女の日常");
+        assert_eq!(clean("SSIS-001 Walk with Yua Mikami today", "SSIS-001", &actors), "Walk with Yua Mikami today");
+    }
+}
@@ -0,0 +1,150 @@
+//! Optional machine translation of titles and plots into the output language, for
+//! titles the sources only describe in another one. The translator is either an
+//! external command or a LibreTranslate-compatible HTTP endpoint (a self-hosted
+//! LibreTranslate, or an LLM server behind the same API). Translations are cached
+//! on disk by text hash, so each text is only sent once.
+
+use anyhow::{bail, Context, Result};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::process::Stdio;
+use std::time::Duration;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+
+use crate::i18n::{self, Lang};
+use crate::types::{AvDetail, Translation};
+use crate::util;
+
+/// Cache state file: `<lang tag>:<text hash>` to translation.
+const CACHE_FILE: &str = "translations.json";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Translator {
+    /// Shell command reading the text on stdin and writing the translation to stdout;
+    /// `AV_TRANSLATE_TO` in its environment holds the target language tag
+    Command(String),
+    /// LibreTranslate-style `POST` endpoint taking `{q, source, target, format}`
+    /// and answering `{translatedText}`
+    Http { url: String, api_key: Option<String> },
+}
+
+impl Translator {
+    /// Translator configured by `AV_TRANSLATE_CMD`, else by `AV_TRANSLATE_URL`
+    /// (with `AV_TRANSLATE_KEY` as its API key).
+    pub fn from_env() -> Option<Self> {
+        let var = |k: &str| std::env::var(k).ok().filter(|v| !v.trim().is_empty());
+        if let Some(cmd) = var("AV_TRANSLATE_CMD") { return Some(Translator::Command(cmd)); }
+        var("AV_TRANSLATE_URL").map(|url| Translator::Http { url, api_key: var("AV_TRANSLATE_KEY") })
+    }
+
+    /// `text` in `target`, uncached.
+    pub async fn translate(&self, text: &str, target: Lang, timeout: Option<Duration>) -> Result<String> {
+        let out = match self {
+            Translator::Command(cmd) => run_command(cmd, text, target, timeout).await?,
+            Translator::Http { url, api_key } => {
+                let mut builder = reqwest::Client::builder();
+                if let Some(t) = timeout { builder = builder.timeout(t); }
+                let mut body = serde_json::json!({ "q": text, "source": "auto", "target": target.tag(), "format": "text" });
+                if let Some(key) = api_key { body["api_key"] = key.as_str().into(); }
+                #[derive(Deserialize)]
+                #[serde(rename_all = "camelCase")]
+                struct Answer {
+                    translated_text: String,
+                }
+                let resp = builder.build()?.post(url).json(&body).send().await?.error_for_status()?;
+                resp.json::<Answer>().await?.translated_text
+            }
+        };
+        let out = out.trim();
+        if out.is_empty() { bail!(i18n::t("err.translate_empty")); }
+        Ok(out.to_string())
+    }
+}
+
+/// Run `cmd` with `text` on stdin; the command is killed when `timeout` passes first.
+async fn run_command(cmd: &str, text: &str, target: Lang, timeout: Option<Duration>) -> Result<String> {
+    let mut command = if cfg!(windows) { Command::new("cmd") } else { Command::new("sh") };
+    command.arg(if cfg!(windows) { "/C" } else { "-c" }).arg(cmd);
+    let mut child = command
+        .env("AV_TRANSLATE_TO", target.tag())
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .with_context(|| i18n::tf("err.translate_start", &[&cmd]))?;
+    let mut stdin = child.stdin.take().expect("piped stdin");
+    let run = async move {
+        stdin.write_all(text.as_bytes()).await?;
+        // Dropping stdin closes it so the command sees the end of the text
+        drop(stdin);
+        child.wait_with_output().await
+    };
+    // A timed-out `run` drops the child, which kills it
+    let output = match timeout {
+        Some(t) => tokio::time::timeout(t, run)
+            .await
+            .map_err(|_| anyhow::anyhow!(i18n::tf("err.translate_timeout", &[&cmd, &t.as_secs()])))??,
+        None => run.await?,
+    };
+    if !output.status.success() {
+        bail!(i18n::tf("err.translate_failed", &[&cmd, &format!("{:?}", output.status.code())]));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// FNV-1a; stable across builds, unlike `std`'s hasher.
+fn hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325, |h, b| (h ^ b as u64).wrapping_mul(0x100000001b3))
+}
+
+/// Fill [`AvDetail::translated`] with the title and plot in `target`, skipping the
+/// fields a source already gave in that language. Failures leave the field out.
+pub(crate) async fn detail(translator: &Translator, target: Lang, timeout: Option<Duration>, d: &mut AvDetail) {
+    let native = d.localized.get(target.tag());
+    let title = if d.clean_title.is_empty() { &d.title } else { &d.clean_title };
+    let title = Some(title.clone()).filter(|t| !t.is_empty() && native.is_none_or(|v| v.title.is_none()));
+    let plot = d.plot.clone().filter(|_| native.is_none_or(|v| v.plot.is_none()));
+    if title.is_none() && plot.is_none() { return; }
+    let mut cache: HashMap<String, String> = util::read_state(CACHE_FILE);
+    let mut changed = false;
+    let mut translate = async |text: Option<String>| {
+        let text = text?;
+        let key = format!("{}:{:016x}", target.tag(), hash(&text));
+        if let Some(hit) = cache.get(&key) { return Some(hit.clone()); }
+        match translator.translate(&text, target, timeout).await {
+            Ok(out) => {
+                cache.insert(key, out.clone());
+                changed = true;
+                Some(out)
+            }
+            Err(e) => {
+                util::debug(format!("translation failed: {:#}", e));
+                None
+            }
+        }
+    };
+    let title = translate(title).await;
+    let plot = translate(plot).await;
+    if changed { util::write_state(CACHE_FILE, &cache); }
+    if title.is_some() || plot.is_some() {
+        d.translated = Some(Translation { lang: target.tag().to_string(), title, plot });
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::time::Instant;
+
+    #[tokio::test]
+    async fn command_runs_and_times_out() {
+        let echo = Translator::Command("tr a-z A-Z".into());
+        assert_eq!(echo.translate("abc", Lang::En, None).await.unwrap(), "ABC");
+        let hung = Translator::Command("sleep 30".into());
+        let started = Instant::now();
+        assert!(hung.translate("abc", Lang::En, Some(Duration::from_millis(200))).await.is_err());
+        assert!(started.elapsed() < Duration::from_secs(5));
+    }
+}
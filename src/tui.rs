@@ -0,0 +1,525 @@
+use anyhow::Result;
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout, Rect};
+use ratatui::style::{Color, Modifier, Style, Stylize};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+use std::collections::HashMap;
+use std::io::Write;
+
+use av::types::{AvDetail, AvItem, Candidate, CastRole};
+use av::{genres, i18n, util, AmbiguousMatch, Client};
+
+use crate::display::{censorship_key, relation_key};
+use crate::history;
+use crate::system;
+
+/// One result list on the navigation stack (search results, an actor's titles, ...).
+struct Listing {
+    title: String,
+    items: Vec<AvItem>,
+    state: ListState,
+}
+
+impl Listing {
+    fn new(title: String, items: Vec<AvItem>) -> Self {
+        let mut state = ListState::default();
+        if !items.is_empty() { state.select(Some(0)); }
+        Self { title, items, state }
+    }
+
+    fn selected(&self) -> Option<&AvItem> {
+        self.state.selected().and_then(|i| self.items.get(i))
+    }
+}
+
+/// Where a title links to: one of its actors, or a keyword search for its studio or series.
+#[derive(Clone)]
+enum Target {
+    Actor(String),
+    Studio(String),
+    Series(String),
+}
+
+impl Target {
+    fn label(&self) -> String {
+        match self {
+            Target::Actor(n) => format!("{}{}", i18n::label("label.actor"), n),
+            Target::Studio(n) => format!("{}{}{}", i18n::label("label.studio"), n, i18n::t("tui.keyword_search")),
+            Target::Series(n) => format!("{}{}{}", i18n::label("label.series"), n, i18n::t("tui.keyword_search")),
+        }
+    }
+}
+
+enum Mode {
+    Browse,
+    Search(String),
+    Goto(Vec<Target>, ListState),
+    /// Titles sharing the selected code, to choose the one to load
+    Pick(String, Vec<Candidate>, ListState),
+}
+
+/// Move a popup list's selection by one row, staying within its `len` rows.
+fn step(state: &mut ListState, len: usize, down: bool) {
+    let next = match state.selected() {
+        Some(i) if down => (i + 1).min(len.saturating_sub(1)),
+        Some(i) => i.saturating_sub(1),
+        None => 0,
+    };
+    state.select(Some(next));
+}
+
+struct App {
+    client: Client,
+    stack: Vec<Listing>,
+    details: HashMap<String, AvDetail>,
+    mode: Mode,
+    detail_scroll: u16,
+    status: String,
+    uncen: bool,
+    quit: bool,
+}
+
+/// `av tui`: browse results on the left, the selected title's detail on the right.
+pub async fn run(client: Client, query: Option<String>, uncen: bool) -> Result<()> {
+    let mut app = App {
+        client,
+        stack: Vec::new(),
+        details: HashMap::new(),
+        mode: Mode::Browse,
+        detail_scroll: 0,
+        status: String::new(),
+        uncen,
+        quit: false,
+    };
+    let mut terminal = ratatui::init();
+    let result = app.main_loop(&mut terminal, query).await;
+    ratatui::restore();
+    result
+}
+
+impl App {
+    async fn main_loop(&mut self, terminal: &mut DefaultTerminal, query: Option<String>) -> Result<()> {
+        match query {
+            Some(q) => self.open_search(terminal, &q).await?,
+            None => {
+                self.loading(terminal, &i18n::t("tui.loading_latest"))?;
+                let items = self.client.latest(50).await.unwrap_or_default();
+                self.push_listing(i18n::t("tui.latest"), items);
+            }
+        }
+        while !self.quit {
+            terminal.draw(|f| self.draw(f))?;
+            if let Event::Key(key) = event::read()? {
+                if key.kind == KeyEventKind::Press {
+                    self.on_key(terminal, key).await?;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Draw once with a status message before a blocking network call.
+    fn loading(&mut self, terminal: &mut DefaultTerminal, msg: &str) -> Result<()> {
+        self.status = msg.to_string();
+        terminal.draw(|f| self.draw(f))?;
+        Ok(())
+    }
+
+    fn push_listing(&mut self, title: String, mut items: Vec<AvItem>) {
+        if self.uncen {
+            items.retain(|i| i.censorship.is_uncensored());
+        }
+        self.status = i18n::tf("tui.count", &[&title, &items.len()]);
+        self.stack.push(Listing::new(title, items));
+        self.detail_scroll = 0;
+    }
+
+    fn current(&mut self) -> Option<&mut Listing> {
+        self.stack.last_mut()
+    }
+
+    fn selected_code(&self) -> Option<String> {
+        self.stack.last().and_then(|l| l.selected()).map(|i| i.code.clone())
+    }
+
+    fn selected_detail(&self) -> Option<&AvDetail> {
+        self.selected_code().and_then(|c| self.details.get(&c))
+    }
+
+    async fn open_search(&mut self, terminal: &mut DefaultTerminal, q: &str) -> Result<()> {
+        self.loading(terminal, &i18n::tf("tui.searching", &[&q]))?;
+        match self.client.search(q).await {
+            Ok(items) => self.push_listing(i18n::tf("tui.search_title", &[&q]), items),
+            Err(e) => self.status = i18n::tf("tui.search_failed", &[&format!("{:#}", e)]),
+        }
+        Ok(())
+    }
+
+    async fn open_target(&mut self, terminal: &mut DefaultTerminal, target: Target) -> Result<()> {
+        self.loading(terminal, &i18n::tf("tui.loading", &[&target.label()]))?;
+        let result = match &target {
+            Target::Actor(name) => self.client.actor_titles(name).await,
+            // No studio/series listing exists across sources; search the name instead
+            Target::Studio(name) | Target::Series(name) => self.client.search(name).await,
+        };
+        match result {
+            Ok(items) => {
+                if let Target::Actor(name) = &target {
+                    history::record(&[], std::slice::from_ref(name));
+                }
+                self.push_listing(target.label(), items);
+            }
+            Err(e) => self.status = i18n::tf("tui.load_failed", &[&format!("{:#}", e)]),
+        }
+        Ok(())
+    }
+
+    async fn load_detail(&mut self, terminal: &mut DefaultTerminal) -> Result<()> {
+        let Some(code) = self.selected_code() else { return Ok(()) };
+        if self.details.contains_key(&code) { return Ok(()); }
+        self.loading(terminal, &i18n::tf("tui.fetching", &[&code]))?;
+        let result = self.client.detail(&code).await;
+        self.show_detail(code, result);
+        Ok(())
+    }
+
+    /// Load the `pick`-th (1-based) candidate for `code` after an ambiguous match.
+    async fn load_candidate(&mut self, terminal: &mut DefaultTerminal, code: String, pick: usize) -> Result<()> {
+        self.loading(terminal, &i18n::tf("tui.fetching", &[&code]))?;
+        let result = self.client.detail_pick(&code, Some(pick)).await;
+        self.show_detail(code, result);
+        Ok(())
+    }
+
+    /// Store a fetched detail under the listed `code`, or offer the candidates when it is ambiguous.
+    fn show_detail(&mut self, code: String, result: Result<AvDetail>) {
+        match result {
+            Ok(d) => {
+                history::record(&[&d.code], &d.actor_names);
+                self.status = i18n::tf("tui.loaded", &[&code]);
+                self.details.insert(code, d);
+            }
+            Err(e) => {
+                self.status = format!("{}{}{:#}", code, i18n::t("punct.colon"), e);
+                if let Some(ambiguous) = e.downcast_ref::<AmbiguousMatch>() {
+                    let mut state = ListState::default();
+                    state.select(Some(0));
+                    self.mode = Mode::Pick(code, ambiguous.candidates.clone(), state);
+                }
+            }
+        }
+        self.detail_scroll = 0;
+    }
+
+    fn browsing(&self) -> bool {
+        matches!(self.mode, Mode::Browse)
+    }
+
+    fn move_selection(&mut self, delta: isize) {
+        let Some(listing) = self.current() else { return };
+        if listing.items.is_empty() { return; }
+        let len = listing.items.len() as isize;
+        let cur = listing.state.selected().unwrap_or(0) as isize;
+        listing.state.select(Some((cur + delta).clamp(0, len - 1) as usize));
+        self.detail_scroll = 0;
+    }
+
+    fn goto_targets(&self) -> Vec<Target> {
+        let Some(d) = self.selected_detail() else { return Vec::new() };
+        let mut targets: Vec<Target> = d.actor_names.iter().cloned().map(Target::Actor).collect();
+        if let Some(s) = &d.studio { targets.push(Target::Studio(s.clone())); }
+        if let Some(s) = &d.series { targets.push(Target::Series(s.clone())); }
+        targets
+    }
+
+    async fn on_key(&mut self, terminal: &mut DefaultTerminal, key: KeyEvent) -> Result<()> {
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(KeyModifiers::CONTROL) {
+            self.quit = true;
+            return Ok(());
+        }
+        match std::mem::replace(&mut self.mode, Mode::Browse) {
+            Mode::Search(mut input) => match key.code {
+                KeyCode::Enter => {
+                    let q = input.trim().to_string();
+                    if !q.is_empty() { self.open_search(terminal, &q).await?; }
+                }
+                KeyCode::Esc => {}
+                KeyCode::Backspace => { input.pop(); self.mode = Mode::Search(input); }
+                KeyCode::Char(ch) => { input.push(ch); self.mode = Mode::Search(input); }
+                _ => self.mode = Mode::Search(input),
+            },
+            Mode::Goto(targets, mut state) => match key.code {
+                KeyCode::Enter => {
+                    if let Some(t) = state.selected().and_then(|i| targets.get(i)).cloned() {
+                        self.open_target(terminal, t).await?;
+                    }
+                }
+                KeyCode::Esc | KeyCode::Char('q') => {}
+                KeyCode::Down | KeyCode::Char('j') => {
+                    step(&mut state, targets.len(), true);
+                    self.mode = Mode::Goto(targets, state);
+                }
+                KeyCode::Up | KeyCode::Char('k') => {
+                    step(&mut state, targets.len(), false);
+                    self.mode = Mode::Goto(targets, state);
+                }
+                _ => self.mode = Mode::Goto(targets, state),
+            },
+            Mode::Pick(code, candidates, mut state) => match key.code {
+                KeyCode::Enter => {
+                    if let Some(i) = state.selected() {
+                        self.load_candidate(terminal, code, i + 1).await?;
+                    }
+                }
+                KeyCode::Esc | KeyCode::Char('q') => {}
+                KeyCode::Down | KeyCode::Char('j') => {
+                    step(&mut state, candidates.len(), true);
+                    self.mode = Mode::Pick(code, candidates, state);
+                }
+                KeyCode::Up | KeyCode::Char('k') => {
+                    step(&mut state, candidates.len(), false);
+                    self.mode = Mode::Pick(code, candidates, state);
+                }
+                _ => self.mode = Mode::Pick(code, candidates, state),
+            },
+            Mode::Browse => self.on_browse_key(terminal, key).await?,
+        }
+        Ok(())
+    }
+
+    async fn on_browse_key(&mut self, terminal: &mut DefaultTerminal, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Char('q') => self.quit = true,
+            KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
+            KeyCode::PageDown => self.move_selection(10),
+            KeyCode::PageUp => self.move_selection(-10),
+            KeyCode::Char('J') => self.detail_scroll = self.detail_scroll.saturating_add(3),
+            KeyCode::Char('K') => self.detail_scroll = self.detail_scroll.saturating_sub(3),
+            KeyCode::Enter | KeyCode::Char('l') => self.load_detail(terminal).await?,
+            KeyCode::Char('/') | KeyCode::Char('s') => self.mode = Mode::Search(String::new()),
+            KeyCode::Char('g') => {
+                self.load_detail(terminal).await?;
+                if !self.browsing() { return Ok(()); }
+                let targets = self.goto_targets();
+                if targets.is_empty() {
+                    self.status = i18n::t("tui.no_targets");
+                } else {
+                    let mut state = ListState::default();
+                    state.select(Some(0));
+                    self.mode = Mode::Goto(targets, state);
+                }
+            }
+            KeyCode::Char('a') => {
+                self.load_detail(terminal).await?;
+                if !self.browsing() { return Ok(()); }
+                if let Some(t) = self.goto_targets().into_iter().find(|t| matches!(t, Target::Actor(_))) {
+                    self.open_target(terminal, t).await?;
+                }
+            }
+            KeyCode::Esc | KeyCode::Backspace | KeyCode::Char('h') if self.stack.len() > 1 => {
+                self.stack.pop();
+                self.detail_scroll = 0;
+                self.status = self.stack.last().map(|l| l.title.clone()).unwrap_or_default();
+            }
+            KeyCode::Char('y') => {
+                if let Some(code) = self.selected_code() {
+                    copy_to_clipboard(&code);
+                    self.status = i18n::tf("tui.copied", &[&code]);
+                }
+            }
+            KeyCode::Char('o') => {
+                self.load_detail(terminal).await?;
+                if !self.browsing() { return Ok(()); }
+                match self.selected_detail().and_then(|d| d.cover_url.clone()) {
+                    Some(url) => {
+                        // The system opener prints a confirmation line; keep it off the alternate screen
+                        self.status = match system::open_browser_url(&url).await {
+                            Ok(()) => i18n::tf("tui.cover_opened", &[&url]),
+                            Err(e) => i18n::tf("tui.cover_failed", &[&format!("{:#}", e)]),
+                        };
+                        terminal.clear()?;
+                    }
+                    None => self.status = i18n::t("tui.no_cover"),
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self, f: &mut Frame) {
+        let [main, footer] = Layout::vertical([Constraint::Min(1), Constraint::Length(2)]).areas(f.area());
+        let [left, right] = Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(55)]).areas(main);
+
+        let breadcrumb = self.stack.iter().map(|l| l.title.as_str()).collect::<Vec<_>>().join(" › ");
+        let list_block = Block::default().borders(Borders::ALL).title(breadcrumb);
+        if let Some(listing) = self.stack.last_mut() {
+            let rows: Vec<ListItem> = listing
+                .items
+                .iter()
+                .map(|i| {
+                    ListItem::new(Line::from(vec![
+                        Span::styled(format!("{:<10} ", i.code), Style::default().fg(Color::Cyan)),
+                        Span::raw(i.title.trim().to_string()),
+                    ]))
+                })
+                .collect();
+            let list = List::new(rows)
+                .block(list_block)
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED))
+                .highlight_symbol("› ");
+            f.render_stateful_widget(list, left, &mut listing.state);
+        } else {
+            f.render_widget(list_block, left);
+        }
+
+        let detail_lines = match self.selected_detail() {
+            Some(d) => detail_lines(d),
+            None => vec![Line::from(i18n::t("tui.press_enter").dark_gray())],
+        };
+        let detail = Paragraph::new(detail_lines)
+            .block(Block::default().borders(Borders::ALL).title(i18n::t("tui.detail")))
+            .wrap(Wrap { trim: false })
+            .scroll((self.detail_scroll, 0));
+        f.render_widget(detail, right);
+
+        let help = match &self.mode {
+            Mode::Search(input) => Line::from(vec![Span::raw(i18n::t("tui.search_prompt")), Span::raw(input.clone()).bold(), Span::raw("▏")]),
+            _ => Line::from(
+                i18n::t("tui.help").dark_gray(),
+            ),
+        };
+        f.render_widget(Paragraph::new(vec![Line::from(self.status.clone()), help]), footer);
+
+        if let Mode::Goto(targets, state) = &mut self.mode {
+            let height = (targets.len() as u16 + 2).min(main.height);
+            let area = centered(main, 50, height);
+            let rows: Vec<ListItem> = targets.iter().map(|t| ListItem::new(t.label())).collect();
+            let list = List::new(rows)
+                .block(Block::default().borders(Borders::ALL).title(i18n::t("tui.goto")))
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+            f.render_widget(Clear, area);
+            f.render_stateful_widget(list, area, state);
+        }
+
+        if let Mode::Pick(_, candidates, state) = &mut self.mode {
+            let height = (candidates.len() as u16 + 2).min(main.height);
+            let area = centered(main, 80, height);
+            let rows: Vec<ListItem> = candidates
+                .iter()
+                .map(|c| {
+                    let mark = if c.score >= 1.0 { "=" } else { "~" };
+                    ListItem::new(Line::from(vec![
+                        Span::raw(format!("{} ", mark)),
+                        Span::styled(format!("{:<10} ", c.code), Style::default().fg(Color::Cyan)),
+                        Span::raw(format!("{} ", c.title.trim())),
+                        Span::styled(format!("[{}]", c.source), Style::default().fg(Color::DarkGray)),
+                    ]))
+                })
+                .collect();
+            let list = List::new(rows)
+                .block(Block::default().borders(Borders::ALL).title(i18n::t("pick.header")))
+                .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+            f.render_widget(Clear, area);
+            f.render_stateful_widget(list, area, state);
+        }
+    }
+}
+
+fn centered(area: Rect, percent_x: u16, height: u16) -> Rect {
+    let width = area.width * percent_x / 100;
+    Rect {
+        x: area.x + (area.width - width) / 2,
+        y: area.y + area.height.saturating_sub(height) / 2,
+        width,
+        height,
+    }
+}
+
+fn field(key: &str, value: String) -> Line<'static> {
+    Line::from(vec![Span::styled(i18n::label(key), Style::default().fg(Color::Yellow)), Span::raw(value)])
+}
+
+/// The fields `display::print_detail_human` shows, in the same order, plus what only the
+/// structured output carries: the rating breakdown, large sample images, every trailer,
+/// the reviews, and the related titles `detail --related` adds.
+fn detail_lines(d: &AvDetail) -> Vec<Line<'_>> {
+    let mut lines = vec![field("label.code", d.code.clone()), field("label.title", d.title.clone())];
+    if !d.clean_title.is_empty() && d.clean_title != d.title { lines.push(field("label.clean_title", d.clean_title.clone())); }
+    if let Some(v) = d.translated.as_ref().and_then(|t| t.title.clone()) { lines.push(field("label.translated", v)); }
+    if !d.actor_names.is_empty() { lines.push(field("label.actors", d.actor_names.join(", "))); }
+    let male: Vec<&str> = d.cast.iter().filter(|m| m.role == CastRole::Actor).map(|m| m.name.as_str()).collect();
+    if !male.is_empty() { lines.push(field("label.male_actors", male.join(", "))); }
+    if let Some(v) = d.release_date { lines.push(field("label.release", v.to_string())); }
+    if let Some(v) = &d.cover_url { lines.push(field("label.cover", v.clone())); }
+    if let Some(v) = d.duration { lines.push(field("label.duration", i18n::tf("fmt.minutes", &[&v.minutes()]))); }
+    if let Some(v) = &d.director { lines.push(field("label.director", v.clone())); }
+    if let Some(v) = &d.studio { lines.push(field("label.studio", v.clone())); }
+    if let Some(v) = &d.label { lines.push(field("label.label", v.clone())); }
+    if let Some(v) = &d.series { lines.push(field("label.series", v.clone())); }
+    if !d.genres.is_empty() { lines.push(field("label.genres", genres::labels(&d.genres).join(", "))); }
+    if let Some(v) = d.rating { lines.push(field("label.rating", v.to_string())); }
+    for (stars, votes) in d.score_distribution.iter().rev() {
+        lines.push(Line::from(format!("  {} {}", "★".repeat(*stars as usize), votes)));
+    }
+    if let Some(key) = censorship_key(d.censorship.category) {
+        let pct = (d.censorship.confidence * 100.0).round();
+        lines.push(field("label.censorship", format!("{} ({}%)", i18n::t(key), pct)));
+    }
+    if let Some(v) = &d.plot {
+        lines.push(field("label.plot", String::new()));
+        lines.extend(v.lines().map(|l| Line::from(l.to_string())));
+    }
+    if let Some(v) = d.translated.as_ref().and_then(|t| t.plot.as_ref()) {
+        lines.push(field("label.translated", String::new()));
+        lines.extend(v.lines().map(|l| Line::from(l.to_string())));
+    }
+    for (key, urls) in [("label.previews", &d.preview_images), ("label.samples", &d.sample_images)] {
+        if urls.is_empty() { continue; }
+        lines.push(field(key, String::new()));
+        lines.extend(urls.iter().enumerate().map(|(i, u)| Line::from(format!("  {}. {}", i + 1, u))));
+    }
+    if !d.trailers.is_empty() {
+        lines.push(field("label.trailer", String::new()));
+        lines.extend(d.trailers.iter().map(|t| Line::from(format!("  {}x{} {}", t.width, t.height, t.url))));
+    }
+    if !d.reviews.is_empty() {
+        lines.push(field("label.reviews", i18n::tf("fmt.count", &[&d.reviews.len()])));
+        for r in &d.reviews {
+            let mut head = vec![format!("[{}]", r.source)];
+            if let Some(s) = r.score { head.push(format!("★{}", s)); }
+            if let Some(a) = &r.author { head.push(a.clone()); }
+            if let Some(date) = r.date { head.push(date.to_string()); }
+            lines.push(Line::from(format!("  {}", head.join(" ")).dark_gray()));
+            lines.extend(r.text.lines().map(|l| Line::from(format!("    {}", l))));
+        }
+    }
+    if !d.magnet_infos.is_empty() || !d.magnets.is_empty() {
+        lines.push(field("label.magnets", i18n::tf("fmt.count", &[&d.magnets.len().max(d.magnet_infos.len())])));
+        for (i, m) in d.magnet_infos.iter().enumerate() {
+            let mut line = format!("  {}. ", i + 1);
+            if let Some(name) = &m.name { line.push_str(name); line.push(' '); }
+            if let Some(size) = &m.size { line.push_str(&format!("| {} ", size)); }
+            if let Some(s) = m.seeders { line.push_str(&format!("| S:{} ", s)); }
+            lines.push(Line::from(line));
+            lines.push(Line::from(format!("     {}", m.url).dark_gray()));
+        }
+    }
+    if !d.related.is_empty() {
+        lines.push(field("label.related", i18n::tf("fmt.count", &[&d.related.len()])));
+        for r in &d.related {
+            lines.push(Line::from(format!("  [{}] {} {}", i18n::t(relation_key(r.relation)), r.item.code, r.item.title.trim())));
+        }
+    }
+    lines
+}
+
+/// Copy via the OSC 52 terminal escape, which works over SSH and needs no clipboard daemon.
+fn copy_to_clipboard(text: &str) {
+    let mut out = std::io::stdout();
+    let _ = write!(out, "\x1b]52;c;{}\x07", util::base64(text.as_bytes()));
+    let _ = out.flush();
+}
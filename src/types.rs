@@ -1,32 +1,256 @@
-use serde::{Deserialize, Serialize};
+use regex::Regex;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::classify::{self, Classification};
+use crate::genres;
+use crate::i18n::Lang;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[non_exhaustive]
 pub struct AvDetail {
     pub code: String,
+    /// Width-normalized title in the preferred language
     pub title: String,
+    /// `title` exactly as the source gave it
+    #[serde(default)]
+    pub original_title: String,
+    /// `title` without the code, actor names, site tags and release markers
+    #[serde(default)]
+    pub clean_title: String,
+    /// Names of the actresses (every performer when a source does not tell them apart)
     pub actor_names: Vec<String>,
-    pub release_date: Option<String>,
+    /// Performers with their role and per-source IDs
+    #[serde(default)]
+    pub cast: Vec<CastMember>,
+    pub release_date: Option<Date>,
     pub cover_url: Option<String>,
     pub plot: Option<String>,
-    pub duration_minutes: Option<u32>,
+    pub duration: Option<Runtime>,
     pub director: Option<String>,
     pub studio: Option<String>,
     pub label: Option<String>,
     pub series: Option<String>,
+    /// Tags as the sources give them
     pub genres: Vec<String>,
-    pub rating: Option<f32>,
+    /// Canonical IDs of `genres` from the [`crate::genres`] taxonomy
+    #[serde(default)]
+    pub genre_ids: Vec<String>,
+    pub rating: Option<Rating>,
+    /// Votes per star count, where the source breaks the rating down
+    #[serde(default)]
+    pub score_distribution: BTreeMap<u8, u32>,
+    /// User reviews shown on the title page; `av reviews` pages through all of them
+    #[serde(default)]
+    pub reviews: Vec<Review>,
+    /// Sample image thumbnails
     pub preview_images: Vec<String>,
+    /// The same samples at full size, where the source has them (DMM)
+    #[serde(default)]
+    pub sample_images: Vec<String>,
+    /// Official trailer in each size the source offers, smallest first (DMM)
+    #[serde(default)]
+    pub trailers: Vec<Trailer>,
     pub magnet_infos: Vec<MagnetInfo>,
     pub magnets: Vec<String>,
+    #[serde(default)]
+    pub censorship: Classification,
+    /// Title, plot, genres and cast per language tag (`en`, `ja`, `zh-Hans`, `zh-Hant`)
+    /// as the sources gave them; the fields above hold the preferred language
+    #[serde(default)]
+    pub localized: BTreeMap<String, Localized>,
+    /// Machine translation of title and plot, when a translator is configured
+    #[serde(default)]
+    pub translated: Option<Translation>,
+    /// Titles the sources list alongside this one
+    #[serde(default)]
+    pub related: Vec<RelatedItem>,
+}
+
+/// Title and plot as a [`crate::translate::Translator`] rendered them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[non_exhaustive]
+pub struct Translation {
+    /// Language tag of the translation
+    pub lang: String,
+    pub title: Option<String>,
+    pub plot: Option<String>,
+}
+
+/// The language-dependent fields of a title in one language.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[non_exhaustive]
+pub struct Localized {
+    pub title: Option<String>,
+    pub plot: Option<String>,
+    pub genres: Vec<String>,
+    pub actor_names: Vec<String>,
+}
+
+impl AvDetail {
+    /// The language-dependent fields as they are now.
+    pub fn snapshot(&self) -> Localized {
+        Localized {
+            title: Some(self.title.clone()).filter(|t| !t.is_empty()),
+            plot: self.plot.clone(),
+            genres: self.genres.clone(),
+            actor_names: self.actor_names.clone(),
+        }
+    }
+
+    /// Switch title, plot, genres and cast to the `lang` variant, field by field where it has them.
+    pub fn localize(&mut self, lang: Lang) {
+        let Some(v) = self.localized.get(lang.tag()) else { return };
+        if let Some(title) = &v.title { self.title = title.clone(); }
+        if v.plot.is_some() { self.plot = v.plot.clone(); }
+        if !v.genres.is_empty() { self.genres = v.genres.clone(); }
+        if !v.actor_names.is_empty() { self.actor_names = v.actor_names.clone(); }
+    }
+
+    /// Take the variants of another source, filling in what ours lack.
+    pub(crate) fn merge_localized(&mut self, other: BTreeMap<String, Localized>) {
+        for (tag, theirs) in other {
+            let ours = self.localized.entry(tag).or_default();
+            if ours.title.is_none() { ours.title = theirs.title; }
+            if ours.plot.is_none() { ours.plot = theirs.plot; }
+            if ours.genres.is_empty() { ours.genres = theirs.genres; }
+            if ours.actor_names.is_empty() { ours.actor_names = theirs.actor_names; }
+        }
+    }
+
+    /// Take another source's cast: IDs and roles of the performers both list, and
+    /// whole roles ours has nobody in (DMM lists actresses only, JavDB the actors too).
+    pub(crate) fn merge_cast(&mut self, other: Vec<CastMember>) {
+        let roles: Vec<CastRole> = self.cast.iter().map(|m| m.role).collect();
+        for theirs in other {
+            match self.cast.iter_mut().find(|m| m.name == theirs.name) {
+                Some(ours) => {
+                    if ours.role == CastRole::Unknown { ours.role = theirs.role; }
+                    for (source, id) in theirs.ids {
+                        ours.ids.entry(source).or_insert(id);
+                    }
+                }
+                None if !roles.contains(&theirs.role) => self.cast.push(theirs),
+                None => {}
+            }
+        }
+    }
+
+    /// Add another source's related titles that are not listed yet.
+    pub(crate) fn merge_related(&mut self, other: Vec<RelatedItem>) {
+        for r in other {
+            if !self.related.iter().any(|ours| ours.item.code == r.item.code) { self.related.push(r); }
+        }
+    }
+
+    /// Whether the title has `genre`: a canonical ID, a localized name or any source's
+    /// tag for it. Tags outside the taxonomy are compared as given.
+    pub fn has_genre(&self, genre: &str) -> bool {
+        match genres::lookup(genre) {
+            Some(g) => self.genre_ids.contains(&g.id),
+            None => self.genres.iter().any(|t| t.eq_ignore_ascii_case(genre.trim())),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[non_exhaustive]
 pub struct AvItem {
     pub code: String,
     pub title: String,
+    #[serde(default)]
+    pub censorship: Classification,
+}
+
+impl AvItem {
+    /// Item classified from its code and title.
+    pub fn new(code: impl Into<String>, title: impl Into<String>) -> Self {
+        let (code, title) = (code.into(), title.into());
+        let censorship = classify::classify(&code, &title, None, &[]);
+        Self { code, title, censorship }
+    }
+}
+
+/// Performer of a title as the metadata panel lists them.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[non_exhaustive]
+pub struct CastMember {
+    pub name: String,
+    #[serde(default)]
+    pub role: CastRole,
+    /// ID on each source that lists the performer, keyed by source name
+    #[serde(default)]
+    pub ids: BTreeMap<String, String>,
+}
+
+impl CastMember {
+    pub fn new(name: impl Into<String>, role: CastRole) -> Self {
+        Self { name: name.into(), role, ids: BTreeMap::new() }
+    }
+
+    /// With `id` on `source` when there is one.
+    pub(crate) fn with_id(mut self, source: &str, id: Option<String>) -> Self {
+        if let Some(id) = id.filter(|i| !i.is_empty()) { self.ids.insert(source.to_string(), id); }
+        self
+    }
+}
+
+/// Female or male performer, from JavDB's ♀/♂ markers and DMM's actress/actor lists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[non_exhaustive]
+pub enum CastRole {
+    Actress,
+    Actor,
+    #[default]
+    Unknown,
+}
+
+/// Title listed alongside another, and why.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[non_exhaustive]
+pub struct RelatedItem {
+    #[serde(flatten)]
+    pub item: AvItem,
+    pub relation: Relation,
+}
+
+impl RelatedItem {
+    pub fn new(item: AvItem, relation: Relation) -> Self {
+        Self { item, relation }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[non_exhaustive]
+pub enum Relation {
+    /// JavDB's "you may also like"
+    Similar,
+    /// JavDB's "people who viewed this also viewed"
+    AlsoViewed,
+    /// Another title of the same series (DMM)
+    SameSeries,
+}
+
+/// Names of the performers that are not known to be male.
+pub(crate) fn actress_names(cast: &[CastMember]) -> Vec<String> {
+    cast.iter().filter(|m| m.role != CastRole::Actor).map(|m| m.name.clone()).collect()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[non_exhaustive]
 pub struct MagnetInfo {
     pub url: String,
     pub name: Option<String>,
@@ -41,8 +265,278 @@ pub struct MagnetInfo {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[non_exhaustive]
 pub struct ActorItem {
     pub name: String,
+    /// Popularity score; 0 when the source has none (DMM)
     pub hot: u32,
 }
 
+/// One page of the actor ranking.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[non_exhaustive]
+pub struct ActorPage {
+    pub actors: Vec<ActorItem>,
+    /// Actors across all pages
+    pub total: usize,
+    pub page: usize,
+    pub per_page: usize,
+}
+
+/// A search hit considered when resolving a code to a single title.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[non_exhaustive]
+pub struct Candidate {
+    pub code: String,
+    pub title: String,
+    pub source: String,
+    pub url: String,
+    pub score: f32,
+}
+
+/// Downloaded artwork (cover) with its MIME type.
+#[derive(Debug, Clone)]
+#[non_exhaustive]
+pub struct Image {
+    pub content_type: String,
+    pub bytes: Vec<u8>,
+}
+
+/// Calendar date of a release, serialized as `YYYY-MM-DD`. Orders chronologically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Date {
+    pub year: u16,
+    pub month: u8,
+    pub day: u8,
+}
+
+impl Date {
+    pub fn new(year: u16, month: u8, day: u8) -> Option<Self> {
+        let leap = year.is_multiple_of(4) && (!year.is_multiple_of(100) || year.is_multiple_of(400));
+        let days = match month {
+            1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+            4 | 6 | 9 | 11 => 30,
+            2 if leap => 29,
+            2 => 28,
+            _ => return None,
+        };
+        (1..=days).contains(&day).then_some(Self { year, month, day })
+    }
+
+    /// First date in `text`, as the sources write it: `2024-01-05`, `2024/1/5`,
+    /// `2024.01.05`, `2024年1月5日`, or with a time after it (`2024-01-05 10:00:00`).
+    pub fn find(text: &str) -> Option<Self> {
+        let re = Regex::new(r"(\d{4})\s*[-/.年]\s*(\d{1,2})\s*[-/.月]\s*(\d{1,2})").unwrap();
+        let caps = re.captures(text)?;
+        Self::new(caps[1].parse().ok()?, caps[2].parse().ok()?, caps[3].parse().ok()?)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl FromStr for Date {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::find(s).ok_or_else(|| format!("not a date: {}", s))
+    }
+}
+
+impl Serialize for Date {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Date {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[cfg(feature = "schema")]
+impl schemars::JsonSchema for Date {
+    fn schema_name() -> std::borrow::Cow<'static, str> {
+        "Date".into()
+    }
+
+    fn json_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+        schemars::json_schema!({ "type": "string", "format": "date" })
+    }
+}
+
+/// Running time, serialized as whole seconds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[serde(transparent)]
+pub struct Runtime(u32);
+
+impl Runtime {
+    pub fn from_secs(secs: u32) -> Self {
+        Self(secs)
+    }
+
+    /// `None` when the minutes do not fit in seconds, as with a mangled page number.
+    pub fn from_minutes(minutes: u32) -> Option<Self> {
+        minutes.checked_mul(60).map(Self)
+    }
+
+    pub fn secs(self) -> u32 {
+        self.0
+    }
+
+    /// Whole minutes, rounded to the nearest.
+    pub fn minutes(self) -> u32 {
+        self.0.saturating_add(30) / 60
+    }
+
+    /// ISO 8601 duration as in JSON-LD: `PT1H40M`, `PT100M`, `PT5970S`, `PT1H39M30S`.
+    pub fn parse_iso8601(s: &str) -> Option<Self> {
+        let re = Regex::new(r"^P(?:(\d+)D)?T(?:(\d+)H)?(?:(\d+)M)?(?:(\d+(?:\.\d+)?)S)?$").unwrap();
+        let caps = re.captures(s.trim())?;
+        let part = |i: usize| caps.get(i).and_then(|m| m.as_str().parse::<f64>().ok()).unwrap_or(0.0);
+        let secs = part(1) * 86400.0 + part(2) * 3600.0 + part(3) * 60.0 + part(4);
+        (secs > 0.0).then(|| Self(secs.round() as u32))
+    }
+}
+
+/// User rating: `value` out of `scale`, from `votes` ratings when the source says.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[non_exhaustive]
+pub struct Rating {
+    pub value: f32,
+    pub scale: f32,
+    pub votes: Option<u32>,
+}
+
+impl Rating {
+    pub fn new(value: f32, scale: f32, votes: Option<u32>) -> Self {
+        Self { value, scale, votes }
+    }
+
+    /// The value rescaled to `scale`, e.g. `out_of(10.0)` for a 10-point display.
+    pub fn out_of(&self, scale: f32) -> f32 {
+        if self.scale <= 0.0 { return 0.0; }
+        self.value / self.scale * scale
+    }
+}
+
+/// Official trailer at one size. `url` is the source's player page; `av trailer`
+/// resolves it to the video file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[non_exhaustive]
+pub struct Trailer {
+    pub width: u32,
+    pub height: u32,
+    pub url: String,
+}
+
+impl Trailer {
+    pub fn new(width: u32, height: u32, url: impl Into<String>) -> Self {
+        Self { width, height, url: url.into() }
+    }
+}
+
+/// User review of a title.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[non_exhaustive]
+pub struct Review {
+    /// Source name (`javdb`, `javlibrary`)
+    pub source: String,
+    pub author: Option<String>,
+    pub date: Option<Date>,
+    /// Stars the reviewer gave, on the source's rating scale
+    pub score: Option<f32>,
+    pub text: String,
+    /// Users who found the review helpful
+    pub likes: Option<u32>,
+}
+
+/// One page of the reviews of a title, from every source that has them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(schemars::JsonSchema))]
+#[non_exhaustive]
+pub struct ReviewPage {
+    pub code: String,
+    pub rating: Option<Rating>,
+    pub score_distribution: BTreeMap<u8, u32>,
+    pub reviews: Vec<Review>,
+    pub page: usize,
+}
+
+impl fmt::Display for Rating {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.2}/{}", self.value, self.scale)?;
+        if let Some(votes) = self.votes { write!(f, " ({})", votes)?; }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sources::defs;
+
+    #[test]
+    fn date_validates_leap_days() {
+        assert!(Date::new(2024, 2, 29).is_some());
+        assert!(Date::new(2000, 2, 29).is_some());
+        assert!(Date::new(2023, 2, 29).is_none());
+        assert!(Date::new(1900, 2, 29).is_none());
+        assert!(Date::new(2024, 4, 31).is_none());
+        assert!(Date::new(2024, 13, 1).is_none());
+        assert!(Date::new(2024, 1, 0).is_none());
+    }
+
+    #[test]
+    fn date_find_and_round_trip() {
+        let date = Date::new(2024, 1, 5).unwrap();
+        for text in ["2024-01-05", "発売日: 2024/1/5", "2024.01.05", "2024年1月5日", "2024-01-05 10:00:00"] {
+            assert_eq!(Date::find(text), Some(date), "{}", text);
+        }
+        assert_eq!(Date::find("2023-02-29"), None);
+        assert_eq!(serde_json::to_string(&date).unwrap(), "\"2024-01-05\"");
+        assert_eq!(serde_json::from_str::<Date>("\"2024-01-05\"").unwrap(), date);
+    }
+
+    #[test]
+    fn runtime_from_iso8601() {
+        assert_eq!(Runtime::parse_iso8601("PT2H5M").map(Runtime::secs), Some(7500));
+        assert_eq!(Runtime::parse_iso8601("PT125M").map(Runtime::minutes), Some(125));
+        assert_eq!(Runtime::parse_iso8601("PT7500S").map(Runtime::minutes), Some(125));
+        assert_eq!(Runtime::parse_iso8601("PT2H4M30S").map(Runtime::minutes), Some(125));
+        assert_eq!(Runtime::parse_iso8601("PT"), None);
+        assert_eq!(Runtime::parse_iso8601("125"), None);
+    }
+
+    #[test]
+    fn runtime_from_page_text() {
+        for (source, key, text) in [("javdb", "page_minutes", "125分"), ("javdb", "page_minutes", "125 min"), ("sukebei", "minutes", "125 min")] {
+            let runtime = defs::get(source).capture::<u32>(key, text).and_then(Runtime::from_minutes);
+            assert_eq!(runtime, Some(Runtime::from_secs(7500)), "{} {}", source, text);
+        }
+        // Bundled patterns stop at three digits; overridden ones may not
+        let huge = Regex::new(r"(\d+)分").unwrap().captures("99999999分").and_then(|c| c[1].parse::<u32>().ok());
+        assert_eq!(huge.and_then(Runtime::from_minutes), None);
+        assert_eq!(Runtime::from_minutes(u32::MAX / 60), Some(Runtime::from_secs(u32::MAX / 60 * 60)));
+        assert_eq!(Runtime::from_secs(u32::MAX).minutes(), u32::MAX / 60);
+    }
+
+    #[test]
+    fn rating_display() {
+        assert_eq!(Rating::new(4.5, 5.0, Some(123)).to_string(), "4.50/5 (123)");
+        assert_eq!(Rating::new(8.0, 10.0, None).to_string(), "8.00/10");
+        assert_eq!(Rating::new(4.0, 5.0, None).out_of(10.0), 8.0);
+        assert_eq!(Rating::new(4.0, 0.0, None).out_of(10.0), 0.0);
+    }
+}